@@ -1,7 +1,8 @@
 use wikipedia_api::*;
 
 #[tokio::main]
-async fn main() -> Result<(), WikiError> {
+async fn main() -> Result<(), WikiError>
+{
     // Search for a page on wikipedia
     let page = Page::search("Programming Language").await?;
 
@@ -0,0 +1,205 @@
+//! Audio files linked on pages, like spoken articles and pronunciations, for
+//! accessibility-focused readers.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{client::Continue, types::deserialize_pages, ImageInfo, WikiClient, WikiError};
+
+/// File extensions of audio formats used on Wikimedia wikis
+const AUDIO_EXTENSIONS: &[&str] = &[".ogg", ".oga", ".opus", ".mp3", ".wav", ".flac", ".mid"];
+
+/// What an [`AudioFile`] is for, guessed from the conventions for its file
+/// name.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum AudioKind
+{
+    /// A recording of the article being read aloud, from the Spoken Wikipedia
+    /// project, e.g. `File:En-Albert Einstein-article.ogg`
+    SpokenArticle,
+
+    /// A pronunciation of a word or name, e.g. `File:En-us-Einstein.ogg` or
+    /// `File:LL-Q150 (fra)-Albert Einstein.wav`
+    Pronunciation,
+
+    /// Any other audio, like music samples
+    #[default]
+    Other,
+}
+
+impl AudioKind
+{
+    /// Guess the kind of the audio file titled `title`
+    fn of(title: &str) -> Self
+    {
+        let name = title.split_once(':').map_or(title, |(_, name)| name);
+        let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem).to_lowercase();
+
+        if stem.contains("spoken") || stem.ends_with("-article") || stem.ends_with(" article") {
+            return Self::SpokenArticle;
+        }
+
+        // Pronunciations are named after their language (and region), like
+        // `De-Albert Einstein`, or Lingua Libre's `LL-Q150 (fra)-...`.
+        let prefix = stem.split('-').next().unwrap_or_default();
+        if stem.starts_with("ll-") || (stem.contains('-') && (2..=3).contains(&prefix.len())) {
+            return Self::Pronunciation;
+        }
+
+        Self::Other
+    }
+}
+
+/// An audio file linked on a page.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudioFile
+{
+    /// Title of the file page, including the `File:` prefix
+    pub title: String,
+
+    /// What the file is for
+    pub kind: AudioKind,
+
+    /// The URLs, size and type of the file
+    pub info: ImageInfo,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RFilePage
+{
+    pub title:     String,
+    #[serde(default)]
+    pub imageinfo: Vec<ImageInfo>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct FilesQuery
+{
+    #[serde(default, deserialize_with = "deserialize_pages")]
+    pub pages: Vec<RFilePage>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct FilesResponse
+{
+    #[serde(rename = "continue")]
+    pub(crate) cont: Option<Continue>,
+    #[serde(default)]
+    pub query:       FilesQuery,
+}
+
+impl WikiClient
+{
+    /// Get the audio files linked on the page titled `title`, like spoken
+    /// versions of the article and pronunciations of its title
+    pub async fn audio_files(&self, title: &str) -> Result<Vec<AudioFile>, WikiError>
+    {
+        let responses: Vec<FilesResponse> = self
+            .get_json_continued(
+                &[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("generator", "images"),
+                    ("titles", title),
+                    ("gimlimit", "max"),
+                    ("prop", "imageinfo"),
+                    ("iiprop", "url|size|mime"),
+                    ("redirects", "1"),
+                    ("formatversion", "2"),
+                ],
+                |x: &FilesResponse| x.cont.clone(),
+            )
+            .await?;
+
+        Ok(to_audio_files(responses))
+    }
+}
+
+/// Pick the audio files out of the file pages of continued responses
+fn to_audio_files(responses: Vec<FilesResponse>) -> Vec<AudioFile>
+{
+    let mut files: Vec<AudioFile> = Vec::new();
+
+    for page in responses.into_iter().flat_map(|x| x.query.pages) {
+        let lower = page.title.to_lowercase();
+        if !AUDIO_EXTENSIONS.iter().any(|x| lower.ends_with(x)) || files.iter().any(|x| x.title == page.title) {
+            continue;
+        }
+
+        // Files in continued batches may come without their info the first
+        // time they are listed.
+        let Some(info) = page.imageinfo.into_iter().next()
+        else {
+            continue;
+        };
+
+        files.push(AudioFile {
+            kind: AudioKind::of(&page.title),
+            title: page.title,
+            info,
+        });
+    }
+
+    files
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{to_audio_files, AudioKind, FilesResponse};
+    use crate::Page;
+
+    #[test]
+    fn test_audio_kind()
+    {
+        assert_eq!(
+            AudioKind::of("File:En-Albert Einstein-article.ogg"),
+            AudioKind::SpokenArticle
+        );
+        assert_eq!(
+            AudioKind::of("File:Albert Einstein (spoken article).ogg"),
+            AudioKind::SpokenArticle
+        );
+        assert_eq!(AudioKind::of("File:De-Albert Einstein.ogg"), AudioKind::Pronunciation);
+        assert_eq!(AudioKind::of("File:En-us-Einstein.ogg"), AudioKind::Pronunciation);
+        assert_eq!(
+            AudioKind::of("File:LL-Q150 (fra)-Albert Einstein.wav"),
+            AudioKind::Pronunciation
+        );
+        assert_eq!(AudioKind::of("File:Einstein speech 1941.ogg"), AudioKind::Other);
+    }
+
+    #[test]
+    fn test_to_audio_files()
+    {
+        let resp: FilesResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"pages":[
+            {"ns":6,"title":"File:Albert Einstein Head.jpg","missing":true,"known":true,"imagerepository":"shared",
+            "imageinfo":[{"size":100,"width":10,"height":10,"url":"https://upload.wikimedia.org/head.jpg",
+            "descriptionurl":"https://commons.wikimedia.org/wiki/File:Albert_Einstein_Head.jpg","mime":"image/jpeg"}]},
+            {"ns":6,"title":"File:De-Albert Einstein.ogg","missing":true,"known":true,"imagerepository":"shared",
+            "imageinfo":[{"size":12000,"width":0,"height":0,"url":"https://upload.wikimedia.org/de.ogg",
+            "descriptionurl":"https://commons.wikimedia.org/wiki/File:De-Albert_Einstein.ogg",
+            "mime":"application/ogg"}]}]}}"#,
+        )
+        .unwrap();
+
+        let files = to_audio_files(vec![resp]);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].title, "File:De-Albert Einstein.ogg");
+        assert_eq!(files[0].kind, AudioKind::Pronunciation);
+        assert_eq!(files[0].info.url, "https://upload.wikimedia.org/de.ogg");
+    }
+
+    #[tokio::test]
+    async fn test_audio_files()
+    {
+        let files = Page::new(
+            "Albert Einstein".to_string(),
+            "https://en.wikipedia.org/wiki/Albert_Einstein".to_string(),
+        )
+        .audio_files()
+        .await
+        .unwrap();
+        assert!(!files.is_empty());
+    }
+}
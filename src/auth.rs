@@ -0,0 +1,112 @@
+//! Credentials for making requests as a logged-in user.
+
+use std::fmt;
+
+/// The session of a logged-in user, sent along with requests whose answer
+/// depends on who is asking, like [`Page::can_edit`](crate::Page::can_edit).
+///
+/// The crate doesn't log in by itself. The session comes either from an OAuth
+/// 2.0 access token, e.g. of an owner-only consumer, or from the session
+/// cookies of a login made elsewhere.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AuthSession
+{
+    credentials: Credentials,
+    bot:         bool,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+enum Credentials
+{
+    Bearer(String),
+    Cookies(String),
+}
+
+impl AuthSession
+{
+    /// A session authenticated with an OAuth 2.0 access token
+    pub fn bearer(token: &str) -> Self
+    {
+        Self {
+            credentials: Credentials::Bearer(token.to_string()),
+            bot:         false,
+        }
+    }
+
+    /// A session authenticated with the value of a `Cookie` header holding the
+    /// session cookies of a login, e.g. `enwikiSession=...`
+    pub fn cookies(cookies: &str) -> Self
+    {
+        Self {
+            credentials: Credentials::Cookies(cookies.to_string()),
+            bot:         false,
+        }
+    }
+
+    /// Whether the user has the `apihighlimits` right, like bots do. Batch
+    /// requests made as such a user hold 500 titles instead of 50.
+    pub fn bot(mut self, bot: bool) -> Self
+    {
+        self.bot = bot;
+        self
+    }
+
+    /// Whether the user was marked with [`Self::bot`] as having the
+    /// `apihighlimits` right
+    pub(crate) fn is_bot(&self) -> bool { self.bot }
+
+    /// Add the credentials to `request`
+    pub(crate) fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder
+    {
+        match &self.credentials {
+            Credentials::Bearer(token) => request.bearer_auth(token),
+            Credentials::Cookies(cookies) => request.header(reqwest::header::COOKIE, cookies),
+        }
+    }
+}
+
+/// Shows the kind of credentials only, so sessions can be logged safely
+impl fmt::Debug for AuthSession
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let kind = match self.credentials {
+            Credentials::Bearer(_) => "Bearer",
+            Credentials::Cookies(_) => "Cookies",
+        };
+        f.debug_struct("AuthSession")
+            .field("credentials", &kind)
+            .field("bot", &self.bot)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::AuthSession;
+
+    #[test]
+    fn test_authorize()
+    {
+        let http = reqwest::Client::new();
+        let request = AuthSession::bearer("secret")
+            .authorize(http.get("https://en.wikipedia.org/w/api.php"))
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()["authorization"], "Bearer secret");
+
+        let request = AuthSession::cookies("enwikiSession=abc")
+            .authorize(http.get("https://en.wikipedia.org/w/api.php"))
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()["cookie"], "enwikiSession=abc");
+    }
+
+    #[test]
+    fn test_debug_hides_credentials()
+    {
+        let debug = format!("{:?}", AuthSession::bearer("secret"));
+        assert!(!debug.contains("secret"));
+    }
+}
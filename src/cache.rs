@@ -0,0 +1,230 @@
+//! The in-memory cache shared by the clones of a
+//! [`WikiClient`](crate::WikiClient).
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{title, SummaryOptions};
+
+/// The number of summaries, and of misses, kept before the oldest is evicted
+const CAPACITY: usize = 1000;
+
+#[derive(Debug)]
+struct CachedSummary
+{
+    revid:   i64,
+    summary: String,
+    fetched: Instant,
+}
+
+/// Summaries keyed by the wiki they're from, normalized title and the options
+/// they were fetched with, and lookups that found nothing, keyed by what was
+/// looked up, the wiki and the normalized title or search term. Caching either
+/// is disabled when there's no TTL for it.
+#[derive(Debug, Default)]
+pub(crate) struct Cache
+{
+    ttl:          Option<Duration>,
+    negative_ttl: Option<Duration>,
+    summaries:    Mutex<HashMap<(String, String, SummaryOptions), CachedSummary>>,
+    misses:       Mutex<HashMap<(&'static str, String, String), Instant>>,
+}
+
+impl Cache
+{
+    pub(crate) fn new(ttl: Option<Duration>) -> Self
+    {
+        Self {
+            ttl,
+            negative_ttl: None,
+            summaries: Mutex::default(),
+            misses: Mutex::default(),
+        }
+    }
+
+    /// Also remember lookups that found nothing for `ttl`
+    pub(crate) fn with_negative_ttl(mut self, ttl: Option<Duration>) -> Self
+    {
+        self.negative_ttl = ttl;
+        self
+    }
+
+    /// Whether the `kind` lookup of `key` on the wiki with the action API
+    /// `wiki`, e.g. the summary of a title, found nothing less than a negative
+    /// TTL ago
+    pub(crate) fn is_miss(&self, kind: &'static str, wiki: &str, key: &str) -> bool
+    {
+        let Some(ttl) = self.negative_ttl
+        else {
+            return false;
+        };
+        let Ok(misses) = self.misses.lock()
+        else {
+            return false;
+        };
+        misses
+            .get(&(kind, wiki.to_string(), title::normalize(key)))
+            .is_some_and(|x| x.elapsed() < ttl)
+    }
+
+    /// Remember that the `kind` lookup of `key` on the wiki with the action API
+    /// `wiki` found nothing
+    pub(crate) fn insert_miss(&self, kind: &'static str, wiki: &str, key: &str)
+    {
+        if self.negative_ttl.is_none() {
+            return;
+        }
+        let Ok(mut misses) = self.misses.lock()
+        else {
+            return;
+        };
+
+        if misses.len() >= CAPACITY {
+            let oldest = misses.iter().min_by_key(|(_, x)| **x).map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                misses.remove(&oldest);
+            }
+        }
+        misses.insert((kind, wiki.to_string(), title::normalize(key)), Instant::now());
+    }
+
+    /// A summary from the wiki with the action API `wiki`, fetched less than a
+    /// TTL ago
+    pub(crate) fn fresh_summary(&self, wiki: &str, title: &str, options: &SummaryOptions) -> Option<String>
+    {
+        let ttl = self.ttl?;
+        let summaries = self.summaries.lock().ok()?;
+        let cached = summaries.get(&summary_key(wiki, title, options))?;
+        (cached.fetched.elapsed() < ttl).then(|| cached.summary.clone())
+    }
+
+    /// A summary of revision `revid` on the wiki with the action API `wiki`,
+    /// however long ago it was fetched
+    pub(crate) fn summary_at(&self, wiki: &str, title: &str, options: &SummaryOptions, revid: i64) -> Option<String>
+    {
+        self.ttl?;
+        let summaries = self.summaries.lock().ok()?;
+        let cached = summaries.get(&summary_key(wiki, title, options))?;
+        (cached.revid == revid).then(|| cached.summary.clone())
+    }
+
+    pub(crate) fn insert_summary(&self, wiki: &str, title: &str, options: &SummaryOptions, revid: i64, summary: &str)
+    {
+        if self.ttl.is_none() {
+            return;
+        }
+        let Ok(mut summaries) = self.summaries.lock()
+        else {
+            return;
+        };
+
+        if summaries.len() >= CAPACITY {
+            let oldest = summaries.iter().min_by_key(|(_, x)| x.fetched).map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                summaries.remove(&oldest);
+            }
+        }
+
+        summaries.insert(
+            summary_key(wiki, title, options),
+            CachedSummary {
+                revid,
+                summary: summary.to_string(),
+                fetched: Instant::now(),
+            },
+        );
+    }
+}
+
+/// The key of the summary of `title` on the wiki with the action API `wiki`.
+/// Revision ids, like titles, are only unique within a wiki.
+fn summary_key(wiki: &str, title: &str, options: &SummaryOptions) -> (String, String, SummaryOptions)
+{
+    (wiki.to_string(), title::normalize(title), options.clone())
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::Duration;
+
+    use super::Cache;
+    use crate::{ExtractFormat, SummaryOptions};
+
+    const EN: &str = "https://en.wikipedia.org/w/api.php";
+    const DE: &str = "https://de.wikipedia.org/w/api.php";
+
+    #[test]
+    fn test_cache_keys_on_normalized_title_and_options()
+    {
+        let cache = Cache::new(Some(Duration::from_secs(60)));
+        let options = SummaryOptions::new();
+        cache.insert_summary(EN, "albert_Einstein", &options, 42, "Albert Einstein was...");
+
+        assert_eq!(
+            cache.fresh_summary(EN, "Albert Einstein", &options).as_deref(),
+            Some("Albert Einstein was...")
+        );
+        assert_eq!(
+            cache.summary_at(EN, "Albert Einstein", &options, 42).as_deref(),
+            Some("Albert Einstein was...")
+        );
+        assert_eq!(cache.summary_at(EN, "Albert Einstein", &options, 43), None);
+
+        let html = SummaryOptions::new().format(ExtractFormat::Html);
+        assert_eq!(cache.fresh_summary(EN, "Albert Einstein", &html), None);
+    }
+
+    #[test]
+    fn test_cache_keys_on_wiki()
+    {
+        let cache = Cache::new(Some(Duration::from_secs(60)));
+        let options = SummaryOptions::new();
+        cache.insert_summary(EN, "X", &options, 42, "X is...");
+
+        assert_eq!(cache.fresh_summary(EN, "X", &options).as_deref(), Some("X is..."));
+        assert_eq!(cache.fresh_summary(DE, "X", &options), None);
+        assert_eq!(cache.summary_at(DE, "X", &options, 42), None);
+    }
+
+    #[test]
+    fn test_cache_disabled_without_ttl()
+    {
+        let cache = Cache::new(None);
+        let options = SummaryOptions::new();
+        cache.insert_summary(EN, "Albert Einstein", &options, 42, "Albert Einstein was...");
+        assert_eq!(cache.summary_at(EN, "Albert Einstein", &options, 42), None);
+    }
+
+    #[test]
+    fn test_cache_remembers_misses()
+    {
+        let cache = Cache::new(None).with_negative_ttl(Some(Duration::from_secs(60)));
+        assert!(!cache.is_miss("summary", EN, "Nowhere at all"));
+        cache.insert_miss("summary", EN, "nowhere_at all");
+        assert!(cache.is_miss("summary", EN, "Nowhere at all"));
+        assert!(!cache.is_miss("search", EN, "Nowhere at all"));
+        assert!(!cache.is_miss("summary", DE, "Nowhere at all"));
+
+        let cache = Cache::new(Some(Duration::from_secs(60)));
+        cache.insert_miss("summary", EN, "Nowhere at all");
+        assert!(!cache.is_miss("summary", EN, "Nowhere at all"));
+
+        let cache = Cache::new(None).with_negative_ttl(Some(Duration::ZERO));
+        cache.insert_miss("summary", EN, "Nowhere at all");
+        assert!(!cache.is_miss("summary", EN, "Nowhere at all"));
+    }
+
+    #[test]
+    fn test_cache_expires_after_ttl()
+    {
+        let cache = Cache::new(Some(Duration::ZERO));
+        let options = SummaryOptions::new();
+        cache.insert_summary(EN, "Albert Einstein", &options, 42, "Albert Einstein was...");
+        assert_eq!(cache.fresh_summary(EN, "Albert Einstein", &options), None);
+        assert!(cache.summary_at(EN, "Albert Einstein", &options, 42).is_some());
+    }
+}
@@ -0,0 +1,338 @@
+//! Walking the category graph, which isn't a tree: categories can be
+//! subcategories of their own subcategories.
+
+use std::collections::{HashSet, VecDeque};
+
+use futures::{stream, Stream};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::Continue,
+    title,
+    types::{deserialize_flag, deserialize_pages},
+    ApiError, Category, WikiClient, WikiError,
+};
+
+/// A category visited by [`WikiClient::category_tree`].
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryNode
+{
+    /// Title of the category, including the `Category:` prefix
+    pub title: String,
+
+    /// The number of steps from the root category, `0` for the root itself
+    pub depth: usize,
+
+    /// The category this one was first found in, `None` for the root
+    pub parent: Option<String>,
+
+    /// All subcategories of the category, including ones visited before. Empty
+    /// for categories at the maximum depth, whose subcategories aren't fetched.
+    pub subcategories: Vec<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RCategoryMember
+{
+    pub title: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SubcategoriesQuery
+{
+    #[serde(default)]
+    pub categorymembers: Vec<RCategoryMember>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SubcategoriesResponse
+{
+    #[serde(rename = "continue")]
+    pub(crate) cont: Option<Continue>,
+    #[serde(default)]
+    pub query:       SubcategoriesQuery,
+    pub error:       Option<ApiError>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RCategoriesPage
+{
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub missing:    bool,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub invalid:    bool,
+    #[serde(default)]
+    pub categories: Vec<Category>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct CategoriesQuery
+{
+    #[serde(default, deserialize_with = "deserialize_pages")]
+    pub pages: Vec<RCategoriesPage>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct CategoriesResponse
+{
+    #[serde(rename = "continue")]
+    pub(crate) cont: Option<Continue>,
+    #[serde(default)]
+    pub query:       CategoriesQuery,
+}
+
+/// The breadth-first order of a category walk, visiting each category once
+#[derive(Debug, Default)]
+struct CategoryWalk
+{
+    max_depth: usize,
+    visited:   HashSet<String>,
+    queue:     VecDeque<CategoryNode>,
+}
+
+impl CategoryWalk
+{
+    fn new(root: &str, max_depth: usize) -> Self
+    {
+        let root = title::normalize(root);
+        Self {
+            max_depth,
+            visited: HashSet::from([root.clone()]),
+            queue: VecDeque::from([CategoryNode {
+                title: root,
+                ..Default::default()
+            }]),
+        }
+    }
+
+    /// Queue the subcategories of `node` that haven't been seen yet, and set
+    /// all of them on the node
+    fn expand(&mut self, node: &mut CategoryNode, subcategories: Vec<String>)
+    {
+        for subcategory in &subcategories {
+            if self.visited.insert(subcategory.clone()) {
+                self.queue.push_back(CategoryNode {
+                    title: subcategory.clone(),
+                    depth: node.depth + 1,
+                    parent: Some(node.title.clone()),
+                    ..Default::default()
+                });
+            }
+        }
+        node.subcategories = subcategories;
+    }
+}
+
+impl WikiClient
+{
+    /// Walk the subcategories of `root` (e.g. `Category:Physics`)
+    /// breadth-first, down to `max_depth` steps from it, visiting each category
+    /// only once no matter how many paths lead to it.
+    ///
+    /// Subcategories are fetched as the stream is consumed, so it can be
+    /// stopped early. A failed request ends the stream after yielding the
+    /// error, which is [`WikiError::PageNotFoundError`] if `root` isn't a
+    /// category title.
+    pub fn category_tree(&self, root: &str, max_depth: usize) -> impl Stream<Item = Result<CategoryNode, WikiError>>
+    {
+        let state = (self.clone(), CategoryWalk::new(root, max_depth), false);
+
+        stream::unfold(state, |(client, mut walk, failed)| {
+            async move {
+                if failed {
+                    return None;
+                }
+
+                let mut node = walk.queue.pop_front()?;
+                if node.depth < walk.max_depth {
+                    match client.subcategories(&node.title).await {
+                        Ok(subcategories) => walk.expand(&mut node, subcategories),
+                        Err(e) => return Some((Err(e), (client, walk, true))),
+                    }
+                }
+
+                Some((Ok(node), (client, walk, false)))
+            }
+        })
+    }
+
+    /// Get the categories the page titled `title` belongs to, including hidden
+    /// maintenance categories
+    pub async fn categories(&self, title: &str) -> Result<Vec<Category>, WikiError>
+    {
+        let responses: Vec<CategoriesResponse> = self
+            .get_json_continued(
+                &[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("prop", "categories"),
+                    ("titles", title),
+                    ("clprop", "hidden"),
+                    ("cllimit", "max"),
+                    ("redirects", "1"),
+                    ("formatversion", "2"),
+                ],
+                |x: &CategoriesResponse| x.cont.clone(),
+            )
+            .await?;
+
+        to_categories(title, responses)
+    }
+
+    /// Get the titles of all subcategories of the category titled `title`
+    async fn subcategories(&self, title: &str) -> Result<Vec<String>, WikiError>
+    {
+        let responses: Vec<SubcategoriesResponse> = self
+            .get_json_continued(
+                &[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("list", "categorymembers"),
+                    ("cmtitle", title),
+                    ("cmtype", "subcat"),
+                    ("cmprop", "title"),
+                    ("cmlimit", "max"),
+                    ("formatversion", "2"),
+                ],
+                |x: &SubcategoriesResponse| x.cont.clone(),
+            )
+            .await?;
+
+        to_subcategories(title, responses)
+    }
+}
+
+/// Collect the subcategories listed by continued responses, failing with
+/// [`WikiError::PageNotFoundError`] if `title` isn't a valid category title,
+/// e.g. because it lacks the `Category:` prefix
+fn to_subcategories(title: &str, responses: Vec<SubcategoriesResponse>) -> Result<Vec<String>, WikiError>
+{
+    let mut subcategories = Vec::new();
+    for resp in responses {
+        match resp.error {
+            Some(e) if e.code == "invalidcategory" || e.code == "invalidtitle" => {
+                return Err(WikiError::PageNotFoundError(title.to_string()))
+            }
+            Some(_) => return Err(WikiError::ResponseError),
+            None => subcategories.extend(resp.query.categorymembers.into_iter().map(|x| x.title)),
+        }
+    }
+    Ok(subcategories)
+}
+
+/// Collect the categories of the one page of continued responses
+fn to_categories(title: &str, responses: Vec<CategoriesResponse>) -> Result<Vec<Category>, WikiError>
+{
+    let mut categories = Vec::new();
+    for page in responses.into_iter().flat_map(|x| x.query.pages) {
+        if page.missing || page.invalid {
+            return Err(WikiError::PageNotFoundError(title.to_string()));
+        }
+        categories.extend(page.categories);
+    }
+    Ok(categories)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use futures::StreamExt;
+
+    use super::{to_categories, to_subcategories, CategoriesResponse, CategoryWalk, SubcategoriesResponse};
+    use crate::{WikiClient, WikiError};
+
+    #[test]
+    fn test_category_walk_stops_at_cycles()
+    {
+        let mut walk = CategoryWalk::new("category:Physics", 5);
+        let subcategories = |titles: &[&str]| titles.iter().map(|x| x.to_string()).collect();
+
+        let mut root = walk.queue.pop_front().unwrap();
+        assert_eq!(root.title, "Category:Physics");
+        walk.expand(&mut root, subcategories(&["Category:Mechanics", "Category:Optics"]));
+
+        let mut mechanics = walk.queue.pop_front().unwrap();
+        assert_eq!(mechanics.depth, 1);
+        assert_eq!(mechanics.parent.as_deref(), Some("Category:Physics"));
+        // A cycle back to the root, and a category already queued
+        walk.expand(&mut mechanics, subcategories(&["Category:Physics", "Category:Optics"]));
+        assert_eq!(mechanics.subcategories.len(), 2);
+
+        let mut optics = walk.queue.pop_front().unwrap();
+        assert_eq!(optics.title, "Category:Optics");
+        walk.expand(&mut optics, subcategories(&["Category:Mechanics"]));
+        assert!(walk.queue.is_empty());
+    }
+
+    #[test]
+    fn test_parse_subcategories()
+    {
+        let resp: SubcategoriesResponse = serde_json::from_str(
+            r#"{"continue":{"cmcontinue":"subcat|4f50|123","continue":"-||"},"query":{"categorymembers":[
+            {"ns":14,"title":"Category:Subfields of physics"},{"ns":14,"title":"Category:Physicists"}]}}"#,
+        )
+        .unwrap();
+        assert!(resp.cont.is_some());
+        assert_eq!(
+            to_subcategories("Category:Physics", vec![resp]).unwrap(),
+            ["Category:Subfields of physics", "Category:Physicists"]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_category()
+    {
+        let resp: SubcategoriesResponse = serde_json::from_str(
+            r#"{"error":{"code":"invalidcategory","info":"The category name you entered is not valid.",
+            "docref":"..."},"servedby":"mw1000"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            to_subcategories("Physics", vec![resp]),
+            Err(WikiError::PageNotFoundError("Physics".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_categories()
+    {
+        let first: CategoriesResponse = serde_json::from_str(
+            r#"{"continue":{"clcontinue":"736|Articles_with_hCards","continue":"||"},"query":{"pages":[
+            {"pageid":736,"ns":0,"title":"Albert Einstein","categories":[{"ns":14,
+            "title":"Category:1879 births"},{"ns":14,"title":"Category:All articles with unsourced statements",
+            "hidden":true}]}]}}"#,
+        )
+        .unwrap();
+        let second: CategoriesResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"pages":[{"pageid":736,"ns":0,"title":"Albert Einstein",
+            "categories":[{"ns":14,"title":"Category:Swiss physicists"}]}]}}"#,
+        )
+        .unwrap();
+
+        let categories = to_categories("Albert Einstein", vec![first, second]).unwrap();
+        assert_eq!(categories.len(), 3);
+        assert!(!categories[0].hidden);
+        assert!(categories[1].hidden);
+        assert_eq!(categories[2].title, "Category:Swiss physicists");
+
+        let missing: CategoriesResponse =
+            serde_json::from_str(r#"{"query":{"pages":[{"ns":0,"title":"Nowhere at all","missing":true}]}}"#).unwrap();
+        assert_eq!(
+            to_categories("Nowhere at all", vec![missing]),
+            Err(WikiError::PageNotFoundError("Nowhere at all".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_category_tree()
+    {
+        let nodes: Vec<_> = WikiClient::new()
+            .category_tree("Category:Physics", 1)
+            .take(10)
+            .collect()
+            .await;
+        let nodes: Vec<_> = nodes.into_iter().map(Result::unwrap).collect();
+        assert_eq!(nodes[0].title, "Category:Physics");
+        assert!(nodes[1..].iter().all(|x| x.depth == 1));
+    }
+}
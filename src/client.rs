@@ -0,0 +1,1017 @@
+//! The [`WikiClient`], which holds the connection pool and the settings shared
+//! by every request.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use once_cell::sync::Lazy;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    cache::Cache, encode_title, logging::Endpoint, stats::Counters, AuthSession, ClientStats, RequestLogging,
+    ResponseFormat, WikiError,
+};
+
+const USER_AGENT: &str = concat!("wikipedia_api/", env!("CARGO_PKG_VERSION"));
+
+static DEFAULT_CLIENT: Lazy<Arc<WikiClient>> = Lazy::new(|| Arc::new(WikiClient::new()));
+
+/// The client for the English Wikipedia used by the convenience functions on
+/// [`Page`](crate::Page) that don't take a client, like
+/// [`Page::search`](crate::Page::search), created on first use.
+///
+/// All of them share its connection pool. Applications that need other
+/// settings build their own [`WikiClient`] and call its methods instead, or
+/// attach it to pages with [`Page::with_client`](crate::Page::with_client).
+pub fn default_client() -> Arc<WikiClient> { Arc::clone(&DEFAULT_CLIENT) }
+
+/// The `continue` object of an action API response, holding the parameters
+/// needed to request the next batch of results.
+///
+/// It serializes to the JSON object the API returned, so it can be stored to
+/// resume a crawl after a restart.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Continue(HashMap<String, serde_json::Value>);
+
+/// One batch of results, with the [`Continue`] to pass to the matching
+/// `resume_*` method to get the next batch.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Paged<T>
+{
+    /// The results of this batch
+    pub items: Vec<T>,
+
+    /// The continuation for the next batch, `None` if this is the last one
+    pub cont: Option<Continue>,
+}
+
+impl Continue
+{
+    /// The continuation parameters, to be added to the original request
+    pub(crate) fn params(&self) -> Vec<(String, String)>
+    {
+        self.0
+            .iter()
+            .map(|(k, v)| {
+                let v = match v {
+                    serde_json::Value::String(x) => x.clone(),
+                    x => x.to_string(),
+                };
+                (k.clone(), v)
+            })
+            .collect()
+    }
+}
+
+/// The maximum number of titles the API accepts in one request
+pub(crate) const MAX_TITLES: usize = 50;
+
+/// The maximum number of titles the API accepts in one request from users with
+/// the `apihighlimits` right, like bots
+pub(crate) const MAX_TITLES_BOT: usize = 500;
+
+/// Split `titles` into batches of at most as many titles as the API accepts in
+/// one request from the user of `session`
+pub(crate) fn title_batches<'a, T>(titles: &'a [T], session: Option<&AuthSession>) -> std::slice::Chunks<'a, T>
+{
+    match session {
+        Some(x) if x.is_bot() => titles.chunks(MAX_TITLES_BOT),
+        _ => titles.chunks(MAX_TITLES),
+    }
+}
+
+/// Add the parameters of `cont` to `params`, to request the batch after the
+/// one `cont` came with
+pub(crate) fn with_continue<'a>(params: &[(&'a str, &'a str)], cont: &'a [(String, String)])
+    -> Vec<(&'a str, &'a str)>
+{
+    let mut all_params = params.to_vec();
+    all_params.extend(cont.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+    all_params
+}
+
+/// Add the `extra` parameters set through an options builder to `params`,
+/// replacing parameters of the same name
+pub(crate) fn with_extra_params<'a>(
+    params: &[(&'a str, &'a str)],
+    extra: &'a [(String, String)],
+) -> Vec<(&'a str, &'a str)>
+{
+    let mut merged: Vec<(&str, &str)> = params
+        .iter()
+        .filter(|(k, _)| !extra.iter().any(|(x, _)| x == k))
+        .copied()
+        .collect();
+    merged.extend(extra.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+    merged
+}
+
+/// Implement the `extra_params` setter of an options builder, which stores the
+/// parameters in its `extra_params: Vec<(String, String)>` field for
+/// [`with_extra_params`]
+macro_rules! extra_params_setter {
+    () => {
+        /// Add raw API parameters to the request, e.g. `&[("exintro", "1")]`,
+        /// replacing any parameter of the same name the crate sets. This is an
+        /// escape hatch for parameters the typed options don't cover yet; the
+        /// response still has to have the shape the crate expects.
+        pub fn extra_params(mut self, params: &[(&str, &str)]) -> Self
+        {
+            self.extra_params
+                .extend(params.iter().map(|(k, v)| (k.to_string(), v.to_string())));
+            self
+        }
+    };
+}
+
+pub(crate) use extra_params_setter;
+
+/// A client for a Wikipedia of one language.
+///
+/// Cloning a `WikiClient` is cheap, and clones share the same connection pool.
+#[derive(Clone, Debug)]
+pub struct WikiClient
+{
+    http:              reqwest::Client,
+    language:          Arc<str>,
+    api_url:           Option<Arc<str>>,
+    rest_url:          Option<Arc<str>>,
+    commons_api_url:   Option<Arc<str>>,
+    wikidata_api_url:  Option<Arc<str>>,
+    sister_api_urls:   Arc<HashMap<String, Arc<str>>>,
+    sister_rest_urls:  Arc<HashMap<String, Arc<str>>>,
+    max_response_size: Option<usize>,
+    response_format:   ResponseFormat,
+    request_logging:   RequestLogging,
+    cache:             Arc<Cache>,
+    stats:             Arc<Counters>,
+}
+
+/// A builder for a [`WikiClient`] with custom settings, e.g. to talk to a
+/// mirror or a local test server instead of wikipedia.org.
+#[derive(Clone, Debug, Default)]
+pub struct WikiClientBuilder
+{
+    language:           Option<String>,
+    api_url:            Option<String>,
+    rest_url:           Option<String>,
+    commons_api_url:    Option<String>,
+    wikidata_api_url:   Option<String>,
+    sister_api_urls:    HashMap<String, String>,
+    sister_rest_urls:   HashMap<String, String>,
+    user_agent:         Option<String>,
+    timeout:            Option<Duration>,
+    cache_ttl:          Option<Duration>,
+    negative_cache_ttl: Option<Duration>,
+    max_response_size:  Option<usize>,
+    response_format:    ResponseFormat,
+    request_logging:    RequestLogging,
+}
+
+impl WikiClientBuilder
+{
+    /// Create a builder with the defaults of [`WikiClient::new`]
+    pub fn new() -> Self { Self::default() }
+
+    /// Use the Wikipedia of `language`, e.g. `de` for de.wikipedia.org
+    pub fn language(mut self, language: &str) -> Self
+    {
+        self.language = Some(language.to_string());
+        self
+    }
+
+    /// Send action API requests to `url` instead of
+    /// `https://<language>.wikipedia.org/w/api.php`
+    pub fn api_url(mut self, url: &str) -> Self
+    {
+        self.api_url = Some(url.to_string());
+        self
+    }
+
+    /// Send REST API requests to `url` instead of
+    /// `https://<language>.wikipedia.org/api/rest_v1`
+    pub fn rest_url(mut self, url: &str) -> Self
+    {
+        self.rest_url = Some(url.to_string());
+        self
+    }
+
+    /// Send Wikimedia Commons requests to `url` instead of
+    /// `https://commons.wikimedia.org/w/api.php`. Commons doesn't use the
+    /// [`Self::api_url`] of Wikipedia.
+    pub fn commons_api_url(mut self, url: &str) -> Self
+    {
+        self.commons_api_url = Some(url.to_string());
+        self
+    }
+
+    /// Send Wikidata requests to `url` instead of
+    /// `https://www.wikidata.org/w/api.php`
+    pub fn wikidata_api_url(mut self, url: &str) -> Self
+    {
+        self.wikidata_api_url = Some(url.to_string());
+        self
+    }
+
+    /// Send action API requests for the sister project `project` (e.g.
+    /// `wikiquote`) to `url` instead of
+    /// `https://<language>.<project>.org/w/api.php`. Sister projects don't use
+    /// the [`Self::api_url`] of Wikipedia.
+    pub fn sister_api_url(mut self, project: &str, url: &str) -> Self
+    {
+        self.sister_api_urls.insert(project.to_string(), url.to_string());
+        self
+    }
+
+    /// Send REST API requests for the sister project `project` (e.g.
+    /// `wiktionary`) to `url` instead of
+    /// `https://<language>.<project>.org/api/rest_v1`. Sister projects don't
+    /// use the [`Self::rest_url`] of Wikipedia.
+    pub fn sister_rest_url(mut self, project: &str, url: &str) -> Self
+    {
+        self.sister_rest_urls.insert(project.to_string(), url.to_string());
+        self
+    }
+
+    /// Send `user_agent` as the User-Agent header. Wikimedia asks for one that
+    /// identifies the application and how to contact its operator.
+    pub fn user_agent(mut self, user_agent: &str) -> Self
+    {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Fail requests with [`WikiError::TimeoutError`] when they take longer
+    /// than `timeout`
+    pub fn timeout(mut self, timeout: Duration) -> Self
+    {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Cache summaries for `ttl`. Without a TTL, which is the default, nothing
+    /// is cached.
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self
+    {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Remember for `ttl` which pages and search terms weren't found, and fail
+    /// with [`WikiError::PageNotFoundError`] right away when they're requested
+    /// again within it, e.g. for autocompletion that repeats the same misses.
+    /// Keep it short, since missing pages may be created at any time. Without
+    /// a TTL, which is the default, misses aren't remembered.
+    pub fn negative_cache_ttl(mut self, ttl: Duration) -> Self
+    {
+        self.negative_cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Fail requests with [`WikiError::ResponseTooLarge`] when the response
+    /// body is larger than `bytes`, instead of reading all of it into memory.
+    /// Without a limit, which is the default, bodies of any size are read.
+    pub fn max_response_size(mut self, bytes: usize) -> Self
+    {
+        self.max_response_size = Some(bytes);
+        self
+    }
+
+    /// Ask the action API for responses in `format`, e.g.
+    /// [`ResponseFormat::Xml`] for a custom wiki that doesn't offer JSON. The
+    /// default is JSON. Responses are decoded by the format they declare
+    /// either way.
+    pub fn response_format(mut self, format: ResponseFormat) -> Self
+    {
+        self.response_format = format;
+        self
+    }
+
+    /// Log requests as `logging` says, e.g. [`RequestLogging::Off`] to not log
+    /// them at all. By default URLs are logged with secrets redacted. See
+    /// [`logging`](crate::logging) for the targets of the events.
+    pub fn request_logging(mut self, logging: RequestLogging) -> Self
+    {
+        self.request_logging = logging;
+        self
+    }
+
+    /// Build the `WikiClient`, failing with [`WikiError::InvalidConfigError`]
+    /// if a URL isn't a valid http(s) URL.
+    pub fn build(self) -> Result<WikiClient, WikiError>
+    {
+        let api_url = self.api_url.as_deref().map(validate_url).transpose()?;
+        let rest_url = self
+            .rest_url
+            .as_deref()
+            .map(|x| validate_url(x.trim_end_matches('/')))
+            .transpose()?;
+        let commons_api_url = self.commons_api_url.as_deref().map(validate_url).transpose()?;
+        let wikidata_api_url = self.wikidata_api_url.as_deref().map(validate_url).transpose()?;
+        let sister_api_urls = self
+            .sister_api_urls
+            .iter()
+            .map(|(project, url)| Ok((project.clone(), validate_url(url)?)))
+            .collect::<Result<_, WikiError>>()?;
+        let sister_rest_urls = self
+            .sister_rest_urls
+            .iter()
+            .map(|(project, url)| Ok((project.clone(), validate_url(url.trim_end_matches('/'))?)))
+            .collect::<Result<_, WikiError>>()?;
+
+        let mut http = reqwest::Client::builder().user_agent(self.user_agent.as_deref().unwrap_or(USER_AGENT));
+        if let Some(timeout) = self.timeout {
+            http = http.timeout(timeout);
+        }
+        let http = match http.build() {
+            Ok(x) => x,
+            Err(e) => return Err(WikiError::InvalidConfigError(e.to_string())),
+        };
+
+        Ok(WikiClient {
+            http,
+            language: Arc::from(self.language.as_deref().unwrap_or("en")),
+            api_url,
+            rest_url,
+            commons_api_url,
+            wikidata_api_url,
+            sister_api_urls: Arc::new(sister_api_urls),
+            sister_rest_urls: Arc::new(sister_rest_urls),
+            max_response_size: self.max_response_size,
+            response_format: self.response_format,
+            request_logging: self.request_logging,
+            cache: Arc::new(Cache::new(self.cache_ttl).with_negative_ttl(self.negative_cache_ttl)),
+            stats: Arc::default(),
+        })
+    }
+}
+
+/// Check that `url` is an absolute http(s) URL
+fn validate_url(url: &str) -> Result<Arc<str>, WikiError>
+{
+    match reqwest::Url::parse(url) {
+        Ok(x) if matches!(x.scheme(), "http" | "https") && x.has_host() => Ok(Arc::from(url)),
+        Ok(_) => Err(WikiError::InvalidConfigError(format!("'{url}' is not an http(s) URL"))),
+        Err(e) => Err(invalid_url(url, e)),
+    }
+}
+
+/// The error for a request to `url`, which isn't a valid URL. Making the
+/// request again can't help, so this isn't a [`WikiError::PageRequestError`].
+fn invalid_url(url: &str, e: impl std::fmt::Display) -> WikiError
+{
+    WikiError::InvalidConfigError(format!("'{url}' is not a valid URL: {e}"))
+}
+
+impl Default for WikiClient
+{
+    fn default() -> Self { Self::new() }
+}
+
+impl WikiClient
+{
+    /// Create a new `WikiClient` for the English Wikipedia
+    pub fn new() -> Self
+    {
+        let http = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            http,
+            language: Arc::from("en"),
+            api_url: None,
+            rest_url: None,
+            commons_api_url: None,
+            wikidata_api_url: None,
+            sister_api_urls: Arc::default(),
+            sister_rest_urls: Arc::default(),
+            max_response_size: None,
+            response_format: ResponseFormat::Json,
+            request_logging: RequestLogging::default(),
+            cache: Arc::default(),
+            stats: Arc::default(),
+        }
+    }
+
+    /// Create a [`WikiClientBuilder`] to configure a `WikiClient`
+    pub fn builder() -> WikiClientBuilder { WikiClientBuilder::new() }
+
+    /// Use the Wikipedia of `language`, e.g. `de` for de.wikipedia.org
+    pub fn with_language(mut self, language: &str) -> Self
+    {
+        self.language = Arc::from(language);
+        self
+    }
+
+    /// The language code of the Wikipedia this client talks to
+    pub fn language(&self) -> &str { &self.language }
+
+    /// The url of the action API (`api.php`)
+    pub fn api_url(&self) -> String
+    {
+        match &self.api_url {
+            Some(x) => x.to_string(),
+            None => format!("https://{}.wikipedia.org/w/api.php", self.language),
+        }
+    }
+
+    /// The url of the REST API, without a trailing slash
+    pub fn rest_url(&self) -> String
+    {
+        match &self.rest_url {
+            Some(x) => x.to_string(),
+            None => format!("https://{}.wikipedia.org/api/rest_v1", self.language),
+        }
+    }
+
+    pub(crate) fn cache(&self) -> &Cache { &self.cache }
+
+    pub(crate) fn counters(&self) -> &Counters { &self.stats }
+
+    /// How much this client and its clones have used Wikipedia so far, e.g.
+    /// the number of requests made
+    pub fn stats(&self) -> ClientStats { self.stats.snapshot() }
+
+    /// The url of the action API of the sister project `project` (e.g.
+    /// `wikiquote`) in the same language, unless one was set for it with
+    /// [`WikiClientBuilder::sister_api_url`]
+    pub(crate) fn sister_api_url(&self, project: &str) -> String
+    {
+        match self.sister_api_urls.get(project) {
+            Some(x) => x.to_string(),
+            None => format!("https://{}.{project}.org/w/api.php", self.language),
+        }
+    }
+
+    /// The url of the action API of Wikimedia Commons, unless one was set with
+    /// [`WikiClientBuilder::commons_api_url`]
+    pub(crate) fn commons_api_url(&self) -> String
+    {
+        match &self.commons_api_url {
+            Some(x) => x.to_string(),
+            None => "https://commons.wikimedia.org/w/api.php".to_string(),
+        }
+    }
+
+    /// The url of the action API of Wikidata, unless one was set with
+    /// [`WikiClientBuilder::wikidata_api_url`]
+    pub(crate) fn wikidata_api_url(&self) -> String
+    {
+        match &self.wikidata_api_url {
+            Some(x) => x.to_string(),
+            None => "https://www.wikidata.org/w/api.php".to_string(),
+        }
+    }
+
+    /// The url of the page titled `title` on the sister project `project`, on
+    /// the same host as its action API
+    pub(crate) fn sister_page_url(&self, project: &str, title: &str) -> String
+    {
+        let api_url = self.sister_api_url(project);
+        let base = api_url
+            .strip_suffix("/w/api.php")
+            .or_else(|| api_url.strip_suffix("/api.php"))
+            .unwrap_or(&api_url);
+        format!("{base}/wiki/{}", encode_title(title))
+    }
+
+    /// The url of the REST API of the sister project `project` (e.g.
+    /// `wiktionary`) in the same language, unless one was set for it with
+    /// [`WikiClientBuilder::sister_rest_url`]
+    pub(crate) fn sister_rest_url(&self, project: &str) -> String
+    {
+        match self.sister_rest_urls.get(project) {
+            Some(x) => x.to_string(),
+            None => format!("https://{}.{project}.org/api/rest_v1", self.language),
+        }
+    }
+
+    /// Make a request to the action API with `params` and parse the JSON
+    /// response into a `T`.
+    pub(crate) async fn get_json<T: DeserializeOwned>(&self, params: &[(&str, &str)]) -> Result<T, WikiError>
+    {
+        self.get_json_at(&self.api_url(), params).await
+    }
+
+    /// Like [`Self::get_json`], but make the request as the user of `session`,
+    /// or anonymously if it's `None`
+    pub(crate) async fn get_json_as<T: DeserializeOwned>(
+        &self,
+        params: &[(&str, &str)],
+        session: Option<&AuthSession>,
+    ) -> Result<T, WikiError>
+    {
+        let request_url = self.action_url(&self.api_url(), params)?;
+        let mut request = self.http.get(request_url.clone());
+        if let Some(session) = session {
+            request = session.authorize(request);
+        }
+        self.send_json(request, &request_url, Endpoint::Action).await
+    }
+
+    /// Like [`Self::get_json`], but make the request to the action API of the
+    /// sister project `project`, e.g. `wikiquote`.
+    pub(crate) async fn get_sister_json<T: DeserializeOwned>(
+        &self,
+        project: &str,
+        params: &[(&str, &str)],
+    ) -> Result<T, WikiError>
+    {
+        self.get_json_at(&self.sister_api_url(project), params).await
+    }
+
+    /// Like [`Self::get_json`], but make the request to the action API at
+    /// `api_url`
+    pub(crate) async fn get_json_at<T: DeserializeOwned>(
+        &self,
+        api_url: &str,
+        params: &[(&str, &str)],
+    ) -> Result<T, WikiError>
+    {
+        let request_url = self.action_url(api_url, params)?;
+        self.get_json_url(request_url, Endpoint::Action).await
+    }
+
+    /// `params` with the `format` the client asks action APIs for, see
+    /// [`WikiClientBuilder::response_format`]
+    fn action_params<'a>(&self, params: &[(&'a str, &'a str)]) -> Vec<(&'a str, &'a str)>
+    {
+        let format = self.response_format.param();
+        params
+            .iter()
+            .map(|&(key, value)| {
+                if key == "format" {
+                    (key, format)
+                }
+                else {
+                    (key, value)
+                }
+            })
+            .collect()
+    }
+
+    /// The URL of a request to the action API at `api_url` with `params`
+    fn action_url(&self, api_url: &str, params: &[(&str, &str)]) -> Result<reqwest::Url, WikiError>
+    {
+        reqwest::Url::parse_with_params(api_url, self.action_params(params)).map_err(|e| invalid_url(api_url, e))
+    }
+
+    /// Make a request to a REST API endpoint, `url` being the full url
+    /// including the path, and parse the JSON response into a `T`.
+    pub(crate) async fn get_rest_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, WikiError>
+    {
+        match reqwest::Url::parse(url) {
+            Ok(x) => self.get_json_url(x, Endpoint::Rest).await,
+            Err(e) => Err(invalid_url(url, e)),
+        }
+    }
+
+    async fn get_json_url<T: DeserializeOwned>(
+        &self,
+        request_url: reqwest::Url,
+        endpoint: Endpoint,
+    ) -> Result<T, WikiError>
+    {
+        self.send_json(self.http.get(request_url.clone()), &request_url, endpoint)
+            .await
+    }
+
+    /// Make a POST request to `url` with `body` as JSON, and parse the JSON
+    /// response into a `T`.
+    #[cfg(feature = "ores")]
+    pub(crate) async fn post_json<B: Serialize, T: DeserializeOwned>(&self, url: &str, body: &B)
+        -> Result<T, WikiError>
+    {
+        match reqwest::Url::parse(url) {
+            Ok(x) => {
+                self.send_json(self.http.post(x.clone()).json(body), &x, Endpoint::Rest)
+                    .await
+            }
+            Err(e) => Err(invalid_url(url, e)),
+        }
+    }
+
+    /// Like [`Self::get_json_at`], but send `params` as a form in a POST
+    /// request, as API modules that store something require
+    pub(crate) async fn post_form_at<T: DeserializeOwned>(
+        &self,
+        api_url: &str,
+        params: &[(&str, &str)],
+    ) -> Result<T, WikiError>
+    {
+        match reqwest::Url::parse(api_url) {
+            Ok(x) => {
+                let params = self.action_params(params);
+                self.send_json(self.http.post(x.clone()).form(&params), &x, Endpoint::Action)
+                    .await
+            }
+            Err(e) => Err(invalid_url(api_url, e)),
+        }
+    }
+
+    /// Follow the redirects from `url` and get the URL they end at
+    pub(crate) async fn redirect_target(&self, url: &str) -> Result<reqwest::Url, WikiError>
+    {
+        let request_url = match reqwest::Url::parse(url) {
+            Ok(x) => x,
+            Err(e) => return Err(invalid_url(url, e)),
+        };
+
+        self.stats.record_request();
+        let resp = match self.http.head(request_url.clone()).send().await {
+            Ok(x) => {
+                self.request_logging.log(Endpoint::Redirect, &request_url);
+                x
+            }
+            Err(e) if e.is_timeout() => return Err(WikiError::TimeoutError),
+            Err(_) => return Err(WikiError::PageRequestError),
+        };
+
+        self.stats.record_status(resp.status().as_u16());
+        if !resp.status().is_success() {
+            return Err(WikiError::HttpStatusError(resp.status().as_u16()));
+        }
+
+        Ok(resp.url().clone())
+    }
+
+    async fn send_json<T: DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+        request_url: &reqwest::Url,
+        endpoint: Endpoint,
+    ) -> Result<T, WikiError>
+    {
+        self.stats.record_request();
+        let mut resp = match request.send().await {
+            Ok(x) => {
+                self.request_logging.log(endpoint, request_url);
+                x
+            }
+            Err(e) if e.is_timeout() => return Err(WikiError::TimeoutError),
+            Err(_) => return Err(WikiError::PageRequestError),
+        };
+
+        self.stats.record_status(resp.status().as_u16());
+        if !resp.status().is_success() {
+            return Err(WikiError::HttpStatusError(resp.status().as_u16()));
+        }
+
+        // The declared length can't be trusted, e.g. with chunked responses,
+        // so the body is also checked as it comes in.
+        let limit = self.max_response_size;
+        if let (Some(limit), Some(len)) = (limit, resp.content_length()) {
+            if len > limit as u64 {
+                return Err(WikiError::ResponseTooLarge(limit));
+            }
+        }
+
+        let content_type = resp
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|x| x.to_str().ok())
+            .map(str::to_string);
+
+        let mut body = Vec::new();
+        loop {
+            match resp.chunk().await {
+                Ok(Some(chunk)) => {
+                    if let Some(limit) = limit.filter(|&limit| body.len() + chunk.len() > limit) {
+                        return Err(WikiError::ResponseTooLarge(limit));
+                    }
+                    self.stats.record_bytes(chunk.len());
+                    body.extend_from_slice(&chunk);
+                }
+                Ok(None) => break,
+                Err(e) if e.is_timeout() => return Err(WikiError::TimeoutError),
+                Err(_) => return Err(WikiError::PageRequestError),
+            }
+        }
+
+        ResponseFormat::of_content_type(content_type.as_deref()).decode(&body)
+    }
+
+    /// Like [`Self::get_json`], but repeat the request with the continuation
+    /// parameters of the previous response until the API reports the result
+    /// as complete. `next` extracts the continuation object from a response.
+    pub(crate) async fn get_json_continued<T: DeserializeOwned>(
+        &self,
+        params: &[(&str, &str)],
+        next: impl Fn(&T) -> Option<Continue>,
+    ) -> Result<Vec<T>, WikiError>
+    {
+        self.get_json_continued_as(params, None, &next).await
+    }
+
+    /// Like [`Self::get_json_continued`], but request `titles` in as many
+    /// batches as the API accepts from the user of `session`, see
+    /// [`title_batches`]. The responses are returned in the order of the
+    /// batches.
+    pub(crate) async fn get_json_batched<T: DeserializeOwned>(
+        &self,
+        params: &[(&str, &str)],
+        titles: &[&str],
+        session: Option<&AuthSession>,
+        next: impl Fn(&T) -> Option<Continue>,
+    ) -> Result<Vec<T>, WikiError>
+    {
+        let mut responses = Vec::new();
+        for batch in title_batches(titles, session) {
+            let batch = batch.join("|");
+            let mut params = params.to_vec();
+            params.push(("titles", &batch));
+            responses.extend(self.get_json_continued_as(&params, session, &next).await?);
+        }
+        Ok(responses)
+    }
+
+    async fn get_json_continued_as<T: DeserializeOwned>(
+        &self,
+        params: &[(&str, &str)],
+        session: Option<&AuthSession>,
+        next: &impl Fn(&T) -> Option<Continue>,
+    ) -> Result<Vec<T>, WikiError>
+    {
+        let mut responses = Vec::new();
+        let mut cont = Vec::new();
+
+        loop {
+            let resp: T = self.get_json_as(&with_continue(params, &cont), session).await?;
+            let next_cont = next(&resp);
+            responses.push(resp);
+
+            match next_cont {
+                Some(x) => cont = x.params(),
+                None => return Ok(responses),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        sync::Arc,
+        thread,
+    };
+
+    use super::{default_client, title_batches, with_extra_params, WikiClient};
+    use crate::{AuthSession, WikiError};
+
+    #[test]
+    fn test_with_extra_params()
+    {
+        let extra = vec![
+            ("exchars".to_string(), "200".to_string()),
+            ("exintro".to_string(), "1".to_string()),
+        ];
+        let params = with_extra_params(&[("action", "query"), ("exchars", "1000")], &extra);
+        assert_eq!(params, [("action", "query"), ("exchars", "200"), ("exintro", "1")]);
+    }
+
+    #[test]
+    fn test_title_batch_boundaries()
+    {
+        let sizes = |n: usize, session: Option<&AuthSession>| {
+            let titles = vec!["Paris"; n];
+            title_batches(&titles, session).map(|x| x.len()).collect::<Vec<_>>()
+        };
+        let bot = AuthSession::bearer("token").bot(true);
+
+        assert!(sizes(0, None).is_empty());
+        assert_eq!(sizes(1, None), [1]);
+        assert_eq!(sizes(50, None), [50]);
+        assert_eq!(sizes(51, None), [50, 1]);
+        assert_eq!(sizes(100, None), [50, 50]);
+        assert_eq!(sizes(501, Some(&AuthSession::bearer("token"))).len(), 11);
+        assert_eq!(sizes(500, Some(&bot)), [500]);
+        assert_eq!(sizes(501, Some(&bot)), [500, 1]);
+    }
+
+    #[test]
+    fn test_default_client_is_shared()
+    {
+        let client = default_client();
+        assert!(Arc::ptr_eq(&client, &default_client()));
+        assert_eq!(client.language(), "en");
+    }
+
+    #[test]
+    fn test_default_urls()
+    {
+        let client = WikiClient::new().with_language("de");
+        assert_eq!(client.api_url(), "https://de.wikipedia.org/w/api.php");
+        assert_eq!(client.rest_url(), "https://de.wikipedia.org/api/rest_v1");
+        assert_eq!(client.sister_api_url("wikiquote"), "https://de.wikiquote.org/w/api.php");
+        assert_eq!(
+            client.sister_rest_url("wiktionary"),
+            "https://de.wiktionary.org/api/rest_v1"
+        );
+    }
+
+    #[test]
+    fn test_builder_custom_urls()
+    {
+        let client = WikiClient::builder()
+            .api_url("http://localhost:8080/w/api.php")
+            .rest_url("http://localhost:8080/api/rest_v1/")
+            .build()
+            .unwrap();
+        assert_eq!(client.api_url(), "http://localhost:8080/w/api.php");
+        assert_eq!(client.rest_url(), "http://localhost:8080/api/rest_v1");
+        assert_eq!(client.language(), "en");
+        assert_eq!(client.commons_api_url(), "https://commons.wikimedia.org/w/api.php");
+
+        let client = WikiClient::builder()
+            .commons_api_url("http://localhost:8083/w/api.php")
+            .wikidata_api_url("http://localhost:8084/w/api.php")
+            .build()
+            .unwrap();
+        assert_eq!(client.commons_api_url(), "http://localhost:8083/w/api.php");
+        assert_eq!(client.wikidata_api_url(), "http://localhost:8084/w/api.php");
+    }
+
+    #[test]
+    fn test_builder_sister_api_urls()
+    {
+        let client = WikiClient::builder()
+            .api_url("http://localhost:8080/w/api.php")
+            .sister_api_url("wikiquote", "http://localhost:8081/w/api.php")
+            .build()
+            .unwrap();
+        assert_eq!(client.sister_api_url("wikiquote"), "http://localhost:8081/w/api.php");
+        assert_eq!(
+            client.sister_api_url("wikisource"),
+            "https://en.wikisource.org/w/api.php"
+        );
+    }
+
+    #[test]
+    fn test_builder_sister_rest_urls()
+    {
+        let client = WikiClient::builder()
+            .rest_url("http://localhost:8080/api/rest_v1")
+            .sister_rest_url("wikivoyage", "http://localhost:8081/api/rest_v1/")
+            .build()
+            .unwrap();
+        assert_eq!(
+            client.sister_rest_url("wiktionary"),
+            "https://en.wiktionary.org/api/rest_v1"
+        );
+        assert_eq!(
+            client.sister_rest_url("wikivoyage"),
+            "http://localhost:8081/api/rest_v1"
+        );
+
+        let err = WikiClient::builder()
+            .sister_rest_url("wiktionary", "not a url")
+            .build()
+            .err()
+            .unwrap();
+        assert!(matches!(err, WikiError::InvalidConfigError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_request_url_is_not_retryable()
+    {
+        let err = WikiClient::new()
+            .get_rest_json::<serde_json::Value>("not a url")
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, WikiError::InvalidConfigError(_)));
+        assert!(!err.is_retryable());
+    }
+
+    /// Serve `response` once on a local port and return the URL to request
+    fn serve_once(response: String) -> String { serve_once_expecting(response, "") }
+
+    /// Like [`serve_once`], but answer with status 400 unless the request line
+    /// contains `expected`
+    fn serve_once_expecting(response: String, expected: &'static str) -> String
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/w/api.php", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 4096];
+            let len = stream.read(&mut request).unwrap_or_default();
+            let request = String::from_utf8_lossy(&request[..len]);
+            let request_line = request.lines().next().unwrap_or_default();
+            if request_line.contains(expected) {
+                let _ = stream.write_all(response.as_bytes());
+            }
+            else {
+                let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n");
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_max_response_size()
+    {
+        let body = format!("{{\"extract\":\"{}\"}}", "a".repeat(1000));
+        let declared = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let chunked = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nTransfer-Encoding: \
+             chunked\r\n\r\n{:x}\r\n{body}\r\n0\r\n\r\n",
+            body.len()
+        );
+
+        for response in [declared.clone(), chunked] {
+            let client = WikiClient::builder()
+                .api_url(&serve_once(response))
+                .max_response_size(100)
+                .build()
+                .unwrap();
+            let err = client.get_json::<serde_json::Value>(&[]).await.err().unwrap();
+            assert_eq!(err, WikiError::ResponseTooLarge(100));
+            assert!(!err.is_retryable());
+        }
+
+        let client = WikiClient::builder()
+            .api_url(&serve_once(declared))
+            .max_response_size(2000)
+            .build()
+            .unwrap();
+        let resp: serde_json::Value = client.get_json(&[]).await.unwrap();
+        assert_eq!(resp["extract"].as_str().map(str::len), Some(1000));
+    }
+
+    #[tokio::test]
+    async fn test_stats()
+    {
+        let body = "{\"batchcomplete\":true}";
+        let ok = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let client = WikiClient::builder().api_url(&serve_once(ok)).build().unwrap();
+        let _: serde_json::Value = client.get_json(&[]).await.unwrap();
+
+        let stats = client.stats();
+        assert_eq!(stats.requests, 1);
+        assert_eq!(stats.bytes_received, body.len() as u64);
+        assert_eq!(stats.throttled, 0);
+
+        let throttled = "HTTP/1.1 429 Too Many Requests\r\nContent-Length: 0\r\n\r\n".to_string();
+        let client = WikiClient::builder().api_url(&serve_once(throttled)).build().unwrap();
+        let err = client.get_json::<serde_json::Value>(&[]).await.err().unwrap();
+        assert_eq!(err, WikiError::HttpStatusError(429));
+        assert_eq!(client.clone().stats().throttled, 1);
+    }
+
+    #[cfg(feature = "xml")]
+    #[tokio::test]
+    async fn test_xml_response()
+    {
+        let body = r#"<?xml version="1.0"?><api><query><pages><page pageid="736" title="Albert Einstein" /></pages></query></api>"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/xml; charset=utf-8\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let client = WikiClient::builder()
+            .api_url(&serve_once_expecting(response.clone(), "format=xml"))
+            .response_format(crate::ResponseFormat::Xml)
+            .build()
+            .unwrap();
+        let params = [("action", "query"), ("format", "json")];
+        let resp: serde_json::Value = client.get_json(&params).await.unwrap();
+        assert_eq!(resp["query"]["pages"]["page"]["title"], "Albert Einstein");
+
+        let api_url = serve_once_expecting(response.clone(), "format=xml");
+        let resp: serde_json::Value = client.get_json_at(&api_url, &params).await.unwrap();
+        assert_eq!(resp["query"]["pages"]["page"]["title"], "Albert Einstein");
+
+        let api_url = serve_once_expecting(response, "format=xml");
+        let client = WikiClient::builder()
+            .api_url(&api_url)
+            .response_format(crate::ResponseFormat::Xml)
+            .build()
+            .unwrap();
+        let resp: serde_json::Value = client.get_json_as(&params, None).await.unwrap();
+        assert_eq!(resp["query"]["pages"]["page"]["title"], "Albert Einstein");
+    }
+
+    #[test]
+    fn test_builder_rejects_invalid_urls()
+    {
+        let err = WikiClient::builder().api_url("not a url").build().err().unwrap();
+        assert!(matches!(err, WikiError::InvalidConfigError(_)));
+
+        let err = WikiClient::builder()
+            .rest_url("ftp://example.org/rest")
+            .build()
+            .err()
+            .unwrap();
+        assert!(matches!(err, WikiError::InvalidConfigError(_)));
+    }
+}
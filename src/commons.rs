@@ -0,0 +1,249 @@
+//! Search for freely licensed media on Wikimedia Commons.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{types::deserialize_pages, ImageInfo, Snippet, WikiClient, WikiError};
+
+/// The kind of media to search for with [`WikiClient::commons_search`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum MediaType
+{
+    /// Files of any type
+    #[default]
+    Any,
+
+    /// Raster images, e.g. JPEG and PNG photos
+    Bitmap,
+
+    /// Vector images, e.g. SVG diagrams
+    Drawing,
+
+    /// Sound files, e.g. Ogg Vorbis recordings
+    Audio,
+
+    /// Video files
+    Video,
+}
+
+impl MediaType
+{
+    /// The CirrusSearch `filetype:` keyword for the type, if it has one
+    pub fn as_filetype(&self) -> Option<&'static str>
+    {
+        match self {
+            Self::Any => None,
+            Self::Bitmap => Some("bitmap"),
+            Self::Drawing => Some("drawing"),
+            Self::Audio => Some("audio"),
+            Self::Video => Some("video"),
+        }
+    }
+}
+
+/// The license of a file, as stated on its description page.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct License
+{
+    /// Short name of the license, e.g. `CC BY-SA 4.0` or `Public domain`
+    pub short_name: String,
+
+    /// The URL of the license text, if it has one
+    pub url: Option<String>,
+
+    /// Plain text of the author credit, if given
+    pub artist: Option<String>,
+
+    /// Whether reusers must credit the author
+    pub attribution_required: bool,
+}
+
+/// A file found on Wikimedia Commons.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaHit
+{
+    /// Title of the file page, including the `File:` prefix
+    pub title: String,
+
+    /// The URLs, size and type of the file
+    pub info: ImageInfo,
+
+    /// The license of the file, if its description page states one
+    pub license: Option<License>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RMetadataValue
+{
+    pub value: serde_json::Value,
+}
+
+impl RMetadataValue
+{
+    /// The value as a string, as values are usually but not always strings
+    fn as_string(&self) -> String
+    {
+        match &self.value {
+            serde_json::Value::String(x) => x.clone(),
+            x => x.to_string(),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RMediaInfo
+{
+    #[serde(flatten)]
+    pub info:        ImageInfo,
+    #[serde(default)]
+    pub extmetadata: HashMap<String, RMetadataValue>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RMediaPage
+{
+    pub title:     String,
+    pub index:     Option<u32>,
+    #[serde(default)]
+    pub imageinfo: Vec<RMediaInfo>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct MediaQuery
+{
+    #[serde(default, deserialize_with = "deserialize_pages")]
+    pub pages: Vec<RMediaPage>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct MediaSearchResponse
+{
+    #[serde(default)]
+    pub query: MediaQuery,
+}
+
+impl MediaHit
+{
+    /// Create the hit for the file page titled `title` from its image info
+    fn new(title: String, info: RMediaInfo) -> Self
+    {
+        let meta = |key: &str| info.extmetadata.get(key).map(RMetadataValue::as_string);
+
+        let license = meta("LicenseShortName").map(|short_name| {
+            License {
+                short_name,
+                url: meta("LicenseUrl"),
+                artist: meta("Artist").map(|x| Snippet::from_html(&x).text.trim().to_string()),
+                attribution_required: meta("AttributionRequired").as_deref() == Some("true"),
+            }
+        });
+
+        Self {
+            title,
+            info: info.info,
+            license,
+        }
+    }
+}
+
+impl WikiClient
+{
+    /// Search Wikimedia Commons for up to `limit` files of `media_type`
+    /// matching `term`, best match first, with their license metadata
+    pub async fn commons_search(
+        &self,
+        term: &str,
+        media_type: MediaType,
+        limit: usize,
+    ) -> Result<Vec<MediaHit>, WikiError>
+    {
+        let search = match media_type.as_filetype() {
+            Some(filetype) => format!("{term} filetype:{filetype}"),
+            None => term.to_string(),
+        };
+
+        let resp: MediaSearchResponse = self
+            .get_json_at(
+                &self.commons_api_url(),
+                &[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("generator", "search"),
+                    ("gsrsearch", &search),
+                    ("gsrnamespace", "6"),
+                    ("gsrlimit", &limit.clamp(1, 50).to_string()),
+                    ("prop", "imageinfo"),
+                    ("iiprop", "url|size|mime|extmetadata"),
+                    (
+                        "iiextmetadatafilter",
+                        "LicenseShortName|LicenseUrl|Artist|AttributionRequired",
+                    ),
+                    ("formatversion", "2"),
+                ],
+            )
+            .await?;
+
+        Ok(to_media_hits(resp.query))
+    }
+}
+
+/// Turn the pages of a search generator query into hits, in search rank order
+fn to_media_hits(query: MediaQuery) -> Vec<MediaHit>
+{
+    let mut pages = query.pages;
+    pages.sort_by_key(|x| x.index.unwrap_or(u32::MAX));
+
+    pages
+        .into_iter()
+        .filter_map(|page| Some(MediaHit::new(page.title, page.imageinfo.into_iter().next()?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{to_media_hits, MediaSearchResponse, MediaType};
+    use crate::WikiClient;
+
+    #[test]
+    fn test_parse_media_search()
+    {
+        let resp: MediaSearchResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"pages":[
+            {"pageid":2,"ns":6,"title":"File:Eiffel Tower at night.jpg","index":2,"imageinfo":[{"size":2000000,
+            "width":3000,"height":4000,"url":"https://upload.wikimedia.org/night.jpg",
+            "descriptionurl":"https://commons.wikimedia.org/wiki/File:Eiffel_Tower_at_night.jpg",
+            "mime":"image/jpeg","extmetadata":{}}]},
+            {"pageid":1,"ns":6,"title":"File:Tour Eiffel.jpg","index":1,"imageinfo":[{"size":1000000,
+            "width":2000,"height":3000,"url":"https://upload.wikimedia.org/tour.jpg",
+            "descriptionurl":"https://commons.wikimedia.org/wiki/File:Tour_Eiffel.jpg","mime":"image/jpeg",
+            "extmetadata":{"LicenseShortName":{"value":"CC BY-SA 4.0","source":"commons-desc-page"},
+            "LicenseUrl":{"value":"https://creativecommons.org/licenses/by-sa/4.0"},
+            "Artist":{"value":"<a href=\"//commons.wikimedia.org/wiki/User:Someone\">Someone</a>"},
+            "AttributionRequired":{"value":"true"}}}]}]}}"#,
+        )
+        .unwrap();
+
+        let hits = to_media_hits(resp.query);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].title, "File:Tour Eiffel.jpg");
+        assert_eq!(hits[0].info.width, 2000);
+        let license = hits[0].license.as_ref().unwrap();
+        assert_eq!(license.short_name, "CC BY-SA 4.0");
+        assert_eq!(license.artist.as_deref(), Some("Someone"));
+        assert!(license.attribution_required);
+        assert!(hits[1].license.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_commons_search()
+    {
+        let hits = WikiClient::new()
+            .commons_search("Eiffel Tower", MediaType::Bitmap, 5)
+            .await
+            .unwrap();
+        assert!(!hits.is_empty());
+        assert!(hits.iter().all(|x| x.info.mime.starts_with("image/")));
+    }
+}
@@ -0,0 +1,422 @@
+//! Decoding of API responses in the formats the action API can send them in.
+//!
+//! Requests ask for [`ResponseFormat::Json`] unless the client is configured
+//! otherwise, but responses are decoded by the format they declare, so a wiki
+//! that only offers XML still works with the `xml` feature.
+
+use serde::de::DeserializeOwned;
+
+use crate::WikiError;
+
+/// The format the action API is asked to send its responses in, see
+/// [`WikiClientBuilder::response_format`](crate::WikiClientBuilder::response_format).
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResponseFormat
+{
+    /// `format=json`, which every Wikimedia wiki offers
+    #[default]
+    Json,
+
+    /// `format=xml`, for custom wikis where JSON isn't available. Responses
+    /// are converted to the shape of the JSON responses, so the same queries
+    /// work, although the API's own XML schema may leave out details.
+    #[cfg(feature = "xml")]
+    Xml,
+}
+
+impl ResponseFormat
+{
+    /// The value of the `format` parameter asking for the format
+    pub(crate) fn param(self) -> &'static str
+    {
+        match self {
+            Self::Json => "json",
+            #[cfg(feature = "xml")]
+            Self::Xml => "xml",
+        }
+    }
+
+    /// The format of a response with the `Content-Type` header `content_type`.
+    /// Anything but XML is taken to be JSON.
+    pub(crate) fn of_content_type(content_type: Option<&str>) -> Self
+    {
+        match content_type {
+            #[cfg(feature = "xml")]
+            Some(x) if x.contains("xml") => Self::Xml,
+            _ => Self::Json,
+        }
+    }
+
+    /// Decode a response body in the format into a `T`
+    pub(crate) fn decode<T: DeserializeOwned>(self, body: &[u8]) -> Result<T, WikiError>
+    {
+        match self {
+            Self::Json => serde_json::from_slice(body).map_err(|_| WikiError::JsonParseError),
+            #[cfg(feature = "xml")]
+            Self::Xml => xml::decode(body),
+        }
+    }
+}
+
+#[cfg(feature = "xml")]
+mod xml
+{
+    use quick_xml::{
+        events::{BytesStart, Event},
+        Reader,
+    };
+    use serde::{
+        de::{
+            self,
+            value::{MapDeserializer, SeqDeserializer},
+            DeserializeOwned, Deserializer, IntoDeserializer, Visitor,
+        },
+        forward_to_deserialize_any,
+    };
+    use serde_json::{Map, Value};
+
+    use crate::WikiError;
+
+    /// Decode an XML response into a `T` by way of [`to_value`] and
+    /// [`Lenient`]
+    pub(super) fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, WikiError>
+    {
+        let value = to_value(body).ok_or(WikiError::JsonParseError)?;
+        T::deserialize(Lenient(value)).map_err(|_| WikiError::JsonParseError)
+    }
+
+    /// An element being read
+    #[derive(Default)]
+    struct Node
+    {
+        name:   String,
+        fields: Map<String, Value>,
+        text:   String,
+    }
+
+    impl Node
+    {
+        fn new(start: &BytesStart) -> Option<Self>
+        {
+            let mut node = Self {
+                name: String::from_utf8(start.name().as_ref().to_vec()).ok()?,
+                ..Self::default()
+            };
+            for attribute in start.attributes() {
+                let attribute = attribute.ok()?;
+                let key = String::from_utf8(attribute.key.as_ref().to_vec()).ok()?;
+                // Whitespace handling is an XML detail without a JSON
+                // counterpart.
+                if key != "xml:space" {
+                    let value = attribute.unescape_value().ok()?.into_owned();
+                    node.fields.insert(key, Value::String(value));
+                }
+            }
+            Some(node)
+        }
+
+        /// Add a child element. Repeated children are collected in an array.
+        fn push(&mut self, name: String, value: Value)
+        {
+            match self.fields.get_mut(&name) {
+                Some(Value::Array(values)) => values.push(value),
+                Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+                None => {
+                    self.fields.insert(name, value);
+                }
+            }
+        }
+
+        /// The value of the element: its text if it has neither attributes
+        /// nor children, otherwise an object of them with any text as
+        /// `content`, like the slots of revisions in JSON
+        fn into_value(mut self) -> Value
+        {
+            if self.fields.is_empty() {
+                return Value::String(self.text);
+            }
+            if !self.text.trim().is_empty() {
+                self.fields.insert("content".to_string(), Value::String(self.text));
+            }
+            Value::Object(self.fields)
+        }
+    }
+
+    /// Convert an XML response to JSON. Attributes and child elements become
+    /// fields, and all values are strings, which [`Lenient`] converts as
+    /// needed. The root `<api>` element becomes the response object.
+    fn to_value(body: &[u8]) -> Option<Value>
+    {
+        let mut reader = Reader::from_reader(body);
+        let mut buf = Vec::new();
+        let mut stack: Vec<Node> = Vec::new();
+
+        loop {
+            let event = reader.read_event_into(&mut buf).ok()?;
+            match event {
+                Event::Start(start) => stack.push(Node::new(&start)?),
+                Event::Empty(start) => {
+                    let node = Node::new(&start)?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.push(node.name.clone(), node.into_value()),
+                        None => return Some(node.into_value()),
+                    }
+                }
+                Event::Text(text) => {
+                    if let Some(node) = stack.last_mut() {
+                        node.text.push_str(&text.unescape().ok()?);
+                    }
+                }
+                Event::CData(text) => {
+                    if let Some(node) = stack.last_mut() {
+                        node.text.push_str(std::str::from_utf8(&text).ok()?);
+                    }
+                }
+                Event::End(_) => {
+                    let node = stack.pop()?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.push(node.name.clone(), node.into_value()),
+                        None => return Some(node.into_value()),
+                    }
+                }
+                Event::Eof => return None,
+                _ => {}
+            }
+            buf.clear();
+        }
+    }
+
+    /// A deserializer of the JSON converted from XML, reading numbers and
+    /// flags from strings and lists from the elements wrapping them, e.g.
+    /// `<pages><page/><page/></pages>` reads as the pages
+    struct Lenient(Value);
+
+    impl<'de> IntoDeserializer<'de, serde_json::Error> for Lenient
+    {
+        type Deserializer = Self;
+
+        fn into_deserializer(self) -> Self { self }
+    }
+
+    impl Lenient
+    {
+        fn parse<T: std::str::FromStr>(&self) -> Result<T, serde_json::Error>
+        {
+            match &self.0 {
+                Value::String(s) => {
+                    s.parse()
+                        .map_err(|_| de::Error::invalid_value(de::Unexpected::Str(s), &"a number"))
+                }
+                _ => Err(de::Error::custom("expected a number")),
+            }
+        }
+
+        fn visit_map<'de, V: Visitor<'de>>(map: Map<String, Value>, visitor: V) -> Result<V::Value, serde_json::Error>
+        {
+            let mut map = MapDeserializer::new(map.into_iter().map(|(k, v)| (k, Lenient(v))));
+            let value = visitor.visit_map(&mut map)?;
+            map.end()?;
+            Ok(value)
+        }
+
+        fn visit_seq<'de, V: Visitor<'de>>(values: Vec<Value>, visitor: V) -> Result<V::Value, serde_json::Error>
+        {
+            let mut seq = SeqDeserializer::new(values.into_iter().map(Lenient));
+            let value = visitor.visit_seq(&mut seq)?;
+            seq.end()?;
+            Ok(value)
+        }
+    }
+
+    macro_rules! deserialize_number {
+        ($($method:ident => $visit:ident,)*) => {
+            $(
+                fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error>
+                {
+                    visitor.$visit(self.parse()?)
+                }
+            )*
+        };
+    }
+
+    impl<'de> Deserializer<'de> for Lenient
+    {
+        type Error = serde_json::Error;
+
+        deserialize_number! {
+            deserialize_i8 => visit_i8,
+            deserialize_i16 => visit_i16,
+            deserialize_i32 => visit_i32,
+            deserialize_i64 => visit_i64,
+            deserialize_u8 => visit_u8,
+            deserialize_u16 => visit_u16,
+            deserialize_u32 => visit_u32,
+            deserialize_u64 => visit_u64,
+            deserialize_f32 => visit_f32,
+            deserialize_f64 => visit_f64,
+        }
+
+        forward_to_deserialize_any! {
+            i128 u128 char str string bytes byte_buf unit unit_struct tuple tuple_struct enum identifier ignored_any
+        }
+
+        fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error>
+        {
+            match self.0 {
+                Value::Object(mut map) => {
+                    // A list in its wrapping element
+                    if map.len() == 1 && map.values().all(Value::is_array) {
+                        match map.values_mut().next().map(Value::take) {
+                            Some(Value::Array(values)) => Self::visit_seq(values, visitor),
+                            _ => unreachable!(),
+                        }
+                    }
+                    else {
+                        Self::visit_map(map, visitor)
+                    }
+                }
+                Value::Array(values) => Self::visit_seq(values, visitor),
+                Value::String(s) => visitor.visit_string(s),
+                other => other.deserialize_any(visitor),
+            }
+        }
+
+        /// Flags are empty attributes, e.g. `missing=""`
+        fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error>
+        {
+            match &self.0 {
+                Value::String(s) => visitor.visit_bool(s != "false"),
+                _ => self.deserialize_any(visitor),
+            }
+        }
+
+        fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error>
+        {
+            match self.0 {
+                Value::Null => visitor.visit_none(),
+                _ => visitor.visit_some(self),
+            }
+        }
+
+        fn deserialize_newtype_struct<V: Visitor<'de>>(
+            self,
+            _: &'static str,
+            visitor: V,
+        ) -> Result<V::Value, Self::Error>
+        {
+            visitor.visit_newtype_struct(self)
+        }
+
+        /// A list is wrapped in an element, whose only field holds the items,
+        /// or a single item if there is one. Empty elements are empty lists.
+        fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error>
+        {
+            match self.0 {
+                Value::Object(map) if map.len() <= 1 => {
+                    match map.into_iter().next().map(|(_, v)| v) {
+                        Some(Value::Array(values)) => Self::visit_seq(values, visitor),
+                        Some(value) => Self::visit_seq(vec![value], visitor),
+                        None => Self::visit_seq(Vec::new(), visitor),
+                    }
+                }
+                Value::String(s) if s.is_empty() => Self::visit_seq(Vec::new(), visitor),
+                other => Lenient(other).deserialize_any(visitor),
+            }
+        }
+
+        fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error>
+        {
+            match self.0 {
+                Value::Object(map) => Self::visit_map(map, visitor),
+                Value::String(s) if s.is_empty() => Self::visit_map(Map::new(), visitor),
+                other => Lenient(other).deserialize_any(visitor),
+            }
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _: &'static str,
+            _: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, Self::Error>
+        {
+            self.deserialize_map(visitor)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests
+    {
+        use serde::Deserialize;
+
+        use super::decode;
+        use crate::{PageId, WikiError};
+
+        #[derive(Debug, Default, Deserialize)]
+        #[serde(default)]
+        struct Response
+        {
+            query: Query,
+        }
+
+        #[derive(Debug, Default, Deserialize)]
+        #[serde(default)]
+        struct Query
+        {
+            #[serde(deserialize_with = "crate::types::deserialize_pages")]
+            pages:      Vec<QueryPage>,
+            normalized: Vec<crate::types::TitleMapping>,
+        }
+
+        #[derive(Debug, Default, Deserialize)]
+        #[serde(default)]
+        struct QueryPage
+        {
+            pageid:  Option<PageId>,
+            title:   String,
+            #[serde(deserialize_with = "crate::types::deserialize_flag")]
+            missing: bool,
+            extract: Option<String>,
+            length:  u64,
+        }
+
+        #[test]
+        fn test_decode_xml()
+        {
+            let xml = br#"<?xml version="1.0"?><api batchcomplete=""><query>
+                <normalized><n from="albert einstein" to="Albert Einstein" /></normalized>
+                <pages>
+                  <page _idx="736" pageid="736" ns="0" title="Albert Einstein" length="190000">
+                    <extract xml:space="preserve">Albert Einstein was a &lt;b&gt;physicist&lt;/b&gt;.</extract>
+                  </page>
+                  <page _idx="-1" ns="0" title="1984 (no such page)" missing="" />
+                </pages></query></api>"#;
+
+            let resp: Response = decode(xml).unwrap();
+            assert_eq!(resp.query.normalized.len(), 1);
+            assert_eq!(resp.query.normalized[0].to, "Albert Einstein");
+
+            let pages = &resp.query.pages;
+            assert_eq!(pages.len(), 2);
+            assert_eq!(pages[0].pageid, Some(PageId(736)));
+            assert_eq!(pages[0].length, 190000);
+            assert_eq!(
+                pages[0].extract.as_deref(),
+                Some("Albert Einstein was a <b>physicist</b>.")
+            );
+            assert!(!pages[0].missing);
+            assert_eq!(pages[1].title, "1984 (no such page)");
+            assert!(pages[1].missing);
+            assert!(pages[1].pageid.is_none());
+
+            let single = br#"<api><query><pages><page pageid="1" title="A" /></pages></query></api>"#;
+            let resp: Response = decode(single).unwrap();
+            assert_eq!(resp.query.pages.len(), 1);
+
+            assert_eq!(
+                decode::<Response>(b"<api><query>").err(),
+                Some(WikiError::JsonParseError)
+            );
+        }
+    }
+}
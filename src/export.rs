@@ -0,0 +1,265 @@
+//! Writing results as JSON Lines or CSV, e.g. for building datasets.
+
+use std::io;
+
+use futures::{AsyncWrite, AsyncWriteExt, Stream, StreamExt};
+use serde::Serialize;
+
+use crate::{types::format_timestamp, PageOverview, SearchHit, WikiError};
+
+/// The format [`export`] and [`export_async`] write.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ExportFormat
+{
+    /// One JSON object per line
+    #[default]
+    JsonLines,
+
+    /// Comma-separated values with a header row, quoted as in RFC 4180.
+    /// Nested values are flattened into columns.
+    Csv,
+}
+
+/// A result that can be exported, as a JSON object or as a CSV row.
+pub trait ExportRecord: Serialize
+{
+    /// The names of the CSV columns
+    fn csv_header() -> &'static [&'static str];
+
+    /// The values of the CSV columns, in the order of [`Self::csv_header`]
+    fn csv_fields(&self) -> Vec<String>;
+}
+
+impl ExportRecord for PageOverview
+{
+    fn csv_header() -> &'static [&'static str] { &["title", "pageid", "url", "extract", "lat", "lon", "thumbnail"] }
+
+    fn csv_fields(&self) -> Vec<String>
+    {
+        vec![
+            self.title.clone(),
+            self.pageid.to_string(),
+            self.url.clone(),
+            self.extract.clone(),
+            self.coordinates.map(|x| x.lat.to_string()).unwrap_or_default(),
+            self.coordinates.map(|x| x.lon.to_string()).unwrap_or_default(),
+            self.thumbnail.as_ref().map(|x| x.source.clone()).unwrap_or_default(),
+        ]
+    }
+}
+
+impl ExportRecord for SearchHit
+{
+    fn csv_header() -> &'static [&'static str]
+    {
+        &[
+            "title",
+            "pageid",
+            "size",
+            "wordcount",
+            "snippet",
+            "timestamp",
+            "aliases",
+            "score",
+        ]
+    }
+
+    /// Aliases are joined with `|`, which can't occur in titles
+    fn csv_fields(&self) -> Vec<String>
+    {
+        vec![
+            self.title.clone(),
+            self.pageid.to_string(),
+            self.size.to_string(),
+            self.wordcount.to_string(),
+            self.snippet.text.clone(),
+            self.timestamp.as_ref().map(format_timestamp).unwrap_or_default(),
+            self.aliases.join("|"),
+            self.score.map(|x| x.to_string()).unwrap_or_default(),
+        ]
+    }
+}
+
+/// Write the results of `items` to `writer` in `format`, e.g. to a file, and
+/// return how many were written.
+///
+/// Stops at the first error of `items`, after writing the results before it.
+/// Write errors are reported as [`WikiError::WriteError`]. The writes block,
+/// so use [`export_async`] to write to an asynchronous writer instead.
+pub async fn export<T, W>(
+    items: impl Stream<Item = Result<T, WikiError>>,
+    format: ExportFormat,
+    mut writer: W,
+) -> Result<usize, WikiError>
+where
+    T: ExportRecord,
+    W: io::Write,
+{
+    let mut items = std::pin::pin!(items);
+    let mut count = 0;
+
+    if format == ExportFormat::Csv {
+        writer
+            .write_all(csv_line(T::csv_header()).as_bytes())
+            .map_err(write_error)?;
+    }
+    while let Some(item) = items.next().await {
+        writer
+            .write_all(render(&item?, format)?.as_bytes())
+            .map_err(write_error)?;
+        count += 1;
+    }
+    writer.flush().map_err(write_error)?;
+
+    Ok(count)
+}
+
+/// Like [`export`], but write to an asynchronous writer
+pub async fn export_async<T, W>(
+    items: impl Stream<Item = Result<T, WikiError>>,
+    format: ExportFormat,
+    mut writer: W,
+) -> Result<usize, WikiError>
+where
+    T: ExportRecord,
+    W: AsyncWrite + Unpin,
+{
+    let mut items = std::pin::pin!(items);
+    let mut count = 0;
+
+    if format == ExportFormat::Csv {
+        writer
+            .write_all(csv_line(T::csv_header()).as_bytes())
+            .await
+            .map_err(write_error)?;
+    }
+    while let Some(item) = items.next().await {
+        writer
+            .write_all(render(&item?, format)?.as_bytes())
+            .await
+            .map_err(write_error)?;
+        count += 1;
+    }
+    writer.flush().await.map_err(write_error)?;
+
+    Ok(count)
+}
+
+/// Render `item` as one line of `format`, including the line break
+fn render<T: ExportRecord>(item: &T, format: ExportFormat) -> Result<String, WikiError>
+{
+    match format {
+        ExportFormat::JsonLines => {
+            let mut line = serde_json::to_string(item).map_err(|_| WikiError::JsonParseError)?;
+            line.push('\n');
+            Ok(line)
+        }
+        ExportFormat::Csv => Ok(csv_line(&item.csv_fields())),
+    }
+}
+
+/// Join `fields` into a CSV row, quoting fields that contain a separator,
+/// quote or line break
+fn csv_line<S: AsRef<str>>(fields: &[S]) -> String
+{
+    let mut line = fields
+        .iter()
+        .map(|x| {
+            let x = x.as_ref();
+            if x.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", x.replace('"', "\"\""))
+            }
+            else {
+                x.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+fn write_error(e: io::Error) -> WikiError { WikiError::WriteError(e.to_string()) }
+
+#[cfg(test)]
+mod tests
+{
+    use futures::stream;
+
+    use super::{export, export_async, ExportFormat};
+    use crate::{Coordinates, PageId, PageOverview, SearchHit, WikiError};
+
+    fn overviews() -> Vec<Result<PageOverview, WikiError>>
+    {
+        vec![
+            Ok(PageOverview {
+                title:       "Eiffel Tower".to_string(),
+                pageid:      PageId(9232),
+                url:         "https://en.wikipedia.org/wiki/Eiffel_Tower".to_string(),
+                extract:     "The Eiffel Tower is a \"wrought-iron\" lattice tower,\nin Paris.".to_string(),
+                coordinates: Some(Coordinates {
+                    lat: 48.8583,
+                    lon: 2.2944,
+                }),
+                thumbnail:   None,
+            }),
+            Ok(PageOverview {
+                title: "Big Ben".to_string(),
+                ..Default::default()
+            }),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_export_csv()
+    {
+        let mut csv = Vec::new();
+        let count = export(stream::iter(overviews()), ExportFormat::Csv, &mut csv)
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "title,pageid,url,extract,lat,lon,thumbnail\r\nEiffel \
+             Tower,9232,https://en.wikipedia.org/wiki/Eiffel_Tower,\"The Eiffel Tower is a \"\"wrought-iron\"\" \
+             lattice tower,\nin Paris.\",48.8583,2.2944,\r\nBig Ben,0,,,,,\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_jsonl_async()
+    {
+        let mut jsonl = Vec::new();
+        let count = export_async(stream::iter(overviews()), ExportFormat::JsonLines, &mut jsonl)
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+
+        let jsonl = String::from_utf8(jsonl).unwrap();
+        let lines: Vec<PageOverview> = jsonl.lines().map(|x| serde_json::from_str(x).unwrap()).collect();
+        assert_eq!(lines, overviews().into_iter().map(Result::unwrap).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_export_stops_at_error()
+    {
+        let hits = vec![
+            Ok(SearchHit {
+                title: "Albert Einstein".to_string(),
+                aliases: vec!["Einstein".to_string(), "A. Einstein".to_string()],
+                ..Default::default()
+            }),
+            Err(WikiError::TimeoutError),
+        ];
+
+        let mut csv = Vec::new();
+        let err = export(stream::iter(hits), ExportFormat::Csv, &mut csv)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err, WikiError::TimeoutError);
+        assert!(String::from_utf8(csv)
+            .unwrap()
+            .ends_with("\r\nAlbert Einstein,0,0,0,,,Einstein|A. Einstein,\r\n"));
+    }
+}
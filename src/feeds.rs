@@ -0,0 +1,562 @@
+//! Feeds of recent activity on the wiki, for patrol tools that watch article
+//! creation and deletions, and of the articles read most.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::{with_continue, Continue},
+    PageId, Paged, Timestamp, WikiClient, WikiError,
+};
+
+/// Options for [`WikiClient::new_pages`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct NewPagesOptions
+{
+    limit:     usize,
+    namespace: i64,
+}
+
+impl Default for NewPagesOptions
+{
+    fn default() -> Self
+    {
+        Self {
+            limit:     50,
+            namespace: 0,
+        }
+    }
+}
+
+impl NewPagesOptions
+{
+    /// Create `NewPagesOptions` with the defaults: the 50 newest articles.
+    pub fn new() -> Self { Self::default() }
+
+    /// The maximum number of pages to return, between 1 and 500.
+    pub fn limit(mut self, limit: usize) -> Self
+    {
+        self.limit = limit.clamp(1, 500);
+        self
+    }
+
+    /// The namespace to watch, `0` being articles.
+    pub fn namespace(mut self, namespace: i64) -> Self
+    {
+        self.namespace = namespace;
+        self
+    }
+}
+
+/// Options for [`WikiClient::log_events`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct LogEventsOptions
+{
+    limit:     usize,
+    log_type:  Option<String>,
+    namespace: Option<i64>,
+}
+
+impl Default for LogEventsOptions
+{
+    fn default() -> Self
+    {
+        Self {
+            limit:     50,
+            log_type:  None,
+            namespace: None,
+        }
+    }
+}
+
+impl LogEventsOptions
+{
+    /// Create `LogEventsOptions` with the defaults: the 50 newest events of any
+    /// type in any namespace.
+    pub fn new() -> Self { Self::default() }
+
+    /// The maximum number of events to return, between 1 and 500.
+    pub fn limit(mut self, limit: usize) -> Self
+    {
+        self.limit = limit.clamp(1, 500);
+        self
+    }
+
+    /// Only return events of one log type, e.g. `delete`, `move` or `protect`.
+    pub fn log_type(mut self, log_type: &str) -> Self
+    {
+        self.log_type = Some(log_type.to_string());
+        self
+    }
+
+    /// Only return events about pages in `namespace`
+    pub fn namespace(mut self, namespace: i64) -> Self
+    {
+        self.namespace = Some(namespace);
+        self
+    }
+}
+
+/// A newly created page.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewPage
+{
+    /// Title of the page
+    pub title: String,
+
+    /// Namespace of the page
+    pub ns: i64,
+
+    /// Id of the page
+    pub pageid: PageId,
+
+    /// Id of the revision that created the page
+    pub revid: i64,
+
+    /// Name of the user who created the page
+    #[serde(default)]
+    pub user: String,
+
+    /// When the page was created
+    pub timestamp: Timestamp,
+
+    /// The edit summary of the first revision
+    #[serde(default)]
+    pub comment: String,
+
+    /// Size of the page in bytes
+    #[serde(default, rename = "newlen")]
+    pub size: u64,
+}
+
+/// An entry in one of the wiki's logs, e.g. a deletion, move or protection.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEvent
+{
+    /// Id of the log entry
+    pub logid: i64,
+
+    /// The log the event belongs to, e.g. `delete`
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    /// What exactly happened, e.g. `delete` or `restore` in the `delete` log
+    pub action: String,
+
+    /// Title of the page the event is about
+    #[serde(default)]
+    pub title: String,
+
+    /// Namespace of the page the event is about
+    #[serde(default)]
+    pub ns: i64,
+
+    /// Id of the page the event is about, `0` if it no longer exists
+    #[serde(default)]
+    pub pageid: PageId,
+
+    /// Name of the user who performed the action
+    #[serde(default)]
+    pub user: String,
+
+    /// When the event happened
+    pub timestamp: Timestamp,
+
+    /// The reason given for the action
+    #[serde(default)]
+    pub comment: String,
+}
+
+/// One of the articles read most on a day.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MostReadArticle
+{
+    /// Title of the page
+    pub title: String,
+
+    /// Id of the page
+    pub pageid: PageId,
+
+    /// Position in the list, starting at 1 for the article read most
+    pub rank: u32,
+
+    /// Number of times the article was viewed on the day
+    pub views: u64,
+
+    /// The short description of the page, if it has one
+    pub description: Option<String>,
+
+    /// The URL of the page
+    pub url: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RContentUrl
+{
+    pub page: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RContentUrls
+{
+    pub desktop: RContentUrl,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RMostReadArticle
+{
+    pub normalizedtitle: String,
+    #[serde(default)]
+    pub pageid:          PageId,
+    pub rank:            u32,
+    pub views:           u64,
+    pub description:     Option<String>,
+    #[serde(default)]
+    pub content_urls:    RContentUrls,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RMostRead
+{
+    #[serde(default)]
+    pub articles: Vec<RMostReadArticle>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct FeaturedResponse
+{
+    pub mostread: Option<RMostRead>,
+}
+
+impl From<RMostReadArticle> for MostReadArticle
+{
+    fn from(article: RMostReadArticle) -> Self
+    {
+        Self {
+            title:       article.normalizedtitle,
+            pageid:      article.pageid,
+            rank:        article.rank,
+            views:       article.views,
+            description: article.description,
+            url:         article.content_urls.desktop.page,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RecentChangesQuery
+{
+    pub recentchanges: Vec<NewPage>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RecentChangesResponse
+{
+    #[serde(rename = "continue")]
+    pub(crate) cont: Option<Continue>,
+    pub query:       RecentChangesQuery,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LogEventsQuery
+{
+    pub logevents: Vec<LogEvent>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LogEventsResponse
+{
+    #[serde(rename = "continue")]
+    pub(crate) cont: Option<Continue>,
+    pub query:       LogEventsQuery,
+}
+
+impl WikiClient
+{
+    /// Get the most recently created pages, newest first
+    pub async fn new_pages(&self, options: &NewPagesOptions) -> Result<Vec<NewPage>, WikiError>
+    {
+        Ok(self.new_pages_paged(options).await?.items)
+    }
+
+    /// Like [`Self::new_pages`], but with the continuation to get older pages
+    /// with [`Self::resume_new_pages`]
+    pub async fn new_pages_paged(&self, options: &NewPagesOptions) -> Result<Paged<NewPage>, WikiError>
+    {
+        self.fetch_new_pages(options, None).await
+    }
+
+    /// Get the next batch of [`Self::new_pages_paged`], continuing from `cont`
+    pub async fn resume_new_pages(
+        &self,
+        options: &NewPagesOptions,
+        cont: &Continue,
+    ) -> Result<Paged<NewPage>, WikiError>
+    {
+        self.fetch_new_pages(options, Some(cont)).await
+    }
+
+    async fn fetch_new_pages(
+        &self,
+        options: &NewPagesOptions,
+        cont: Option<&Continue>,
+    ) -> Result<Paged<NewPage>, WikiError>
+    {
+        let namespace = options.namespace.to_string();
+        let limit = options.limit.to_string();
+        let cont = cont.map(Continue::params).unwrap_or_default();
+
+        let resp: RecentChangesResponse = self
+            .get_json(&with_continue(
+                &[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("list", "recentchanges"),
+                    ("rctype", "new"),
+                    ("rcprop", "title|ids|user|timestamp|comment|sizes"),
+                    ("rcnamespace", &namespace),
+                    ("rclimit", &limit),
+                    ("formatversion", "2"),
+                ],
+                &cont,
+            ))
+            .await?;
+
+        Ok(Paged {
+            items: resp.query.recentchanges,
+            cont:  resp.cont,
+        })
+    }
+
+    /// Get the most recent log events, newest first
+    pub async fn log_events(&self, options: &LogEventsOptions) -> Result<Vec<LogEvent>, WikiError>
+    {
+        Ok(self.log_events_paged(options).await?.items)
+    }
+
+    /// Like [`Self::log_events`], but with the continuation to get older events
+    /// with [`Self::resume_log_events`]
+    pub async fn log_events_paged(&self, options: &LogEventsOptions) -> Result<Paged<LogEvent>, WikiError>
+    {
+        self.fetch_log_events(options, None).await
+    }
+
+    /// Get the next batch of [`Self::log_events_paged`], continuing from `cont`
+    pub async fn resume_log_events(
+        &self,
+        options: &LogEventsOptions,
+        cont: &Continue,
+    ) -> Result<Paged<LogEvent>, WikiError>
+    {
+        self.fetch_log_events(options, Some(cont)).await
+    }
+
+    async fn fetch_log_events(
+        &self,
+        options: &LogEventsOptions,
+        cont: Option<&Continue>,
+    ) -> Result<Paged<LogEvent>, WikiError>
+    {
+        let limit = options.limit.to_string();
+        let namespace = options.namespace.map(|x| x.to_string());
+        let cont = cont.map(Continue::params).unwrap_or_default();
+
+        let mut params = vec![
+            ("action", "query"),
+            ("format", "json"),
+            ("list", "logevents"),
+            ("leprop", "ids|title|type|user|timestamp|comment"),
+            ("lelimit", &limit),
+            ("formatversion", "2"),
+        ];
+        if let Some(log_type) = &options.log_type {
+            params.push(("letype", log_type));
+        }
+        if let Some(namespace) = &namespace {
+            params.push(("lenamespace", namespace));
+        }
+
+        let resp: LogEventsResponse = self.get_json(&with_continue(&params, &cont)).await?;
+        Ok(Paged {
+            items: resp.query.logevents,
+            cont:  resp.cont,
+        })
+    }
+
+    /// Get the articles of the Wikipedia of `lang` read most, most read first,
+    /// from the featured feed of the day of `date`. The feed of a day lists
+    /// the articles read most on the day before, the last complete one.
+    ///
+    /// Fails with [`WikiError::PageNotFoundError`] if there's no list for
+    /// `date` yet.
+    pub async fn most_read(&self, date: &Timestamp, lang: &str) -> Result<Vec<MostReadArticle>, WikiError>
+    {
+        let date = feed_date(date);
+        let url = format!("{}/feed/featured/{date}", self.clone().with_language(lang).rest_url());
+
+        let resp: FeaturedResponse = match self.get_rest_json(&url).await {
+            Ok(x) => x,
+            Err(WikiError::HttpStatusError(404)) => return Err(most_read_not_found(&date)),
+            Err(e) => return Err(e),
+        };
+
+        to_most_read(&date, resp)
+    }
+}
+
+/// Format `timestamp` as the `yyyy/mm/dd` date of feed URLs
+#[cfg(feature = "chrono")]
+fn feed_date(timestamp: &Timestamp) -> String { timestamp.format("%Y/%m/%d").to_string() }
+
+/// Format `timestamp` as the `yyyy/mm/dd` date of feed URLs
+#[cfg(not(feature = "chrono"))]
+fn feed_date(timestamp: &Timestamp) -> String { timestamp.get(..10).unwrap_or(timestamp).replace('-', "/") }
+
+fn most_read_not_found(date: &str) -> WikiError
+{
+    WikiError::PageNotFoundError(format!("most read articles of {date}"))
+}
+
+/// Get the articles out of the response of [`WikiClient::most_read`]
+fn to_most_read(date: &str, resp: FeaturedResponse) -> Result<Vec<MostReadArticle>, WikiError>
+{
+    match resp.mostread {
+        Some(x) => Ok(x.articles.into_iter().map(MostReadArticle::from).collect()),
+        None => Err(most_read_not_found(date)),
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{
+        feed_date, to_most_read, FeaturedResponse, LogEventsOptions, LogEventsResponse, NewPagesOptions,
+        RecentChangesResponse,
+    };
+    use crate::{types::parse_timestamp, Continue, WikiClient, WikiError};
+
+    #[test]
+    fn test_parse_new_pages()
+    {
+        let resp: RecentChangesResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"continue":{"rccontinue":"20230501120000|1","continue":"-||"},
+            "query":{"recentchanges":[{"type":"new","ns":0,"title":"Example","pageid":73500000,
+            "revid":1152700000,"old_revid":0,"rcid":1630000000,"user":"Someone","oldlen":0,"newlen":2048,
+            "timestamp":"2023-05-01T12:00:00Z","comment":"Created page"}]}}"#,
+        )
+        .unwrap();
+        let page = &resp.query.recentchanges[0];
+        assert_eq!(page.title, "Example");
+        assert_eq!(page.size, 2048);
+        assert_eq!(page.user, "Someone");
+    }
+
+    #[test]
+    fn test_continue_roundtrip()
+    {
+        let resp: RecentChangesResponse = serde_json::from_str(
+            r#"{"continue":{"rccontinue":"20230501120000|1","continue":"-||"},"query":{"recentchanges":[]}}"#,
+        )
+        .unwrap();
+
+        // The continuation survives being stored and loaded again.
+        let cont = resp.cont.unwrap();
+        let stored = serde_json::to_string(&cont).unwrap();
+        let loaded: Continue = serde_json::from_str(&stored).unwrap();
+        assert_eq!(loaded, cont);
+        assert!(loaded
+            .params()
+            .contains(&("rccontinue".to_string(), "20230501120000|1".to_string())));
+    }
+
+    #[test]
+    fn test_parse_log_events()
+    {
+        let resp: LogEventsResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"logevents":[{"logid":150000000,"ns":0,"title":"Spam page",
+            "pageid":0,"logpage":0,"type":"delete","action":"delete","user":"Admin",
+            "timestamp":"2023-05-01T12:00:00Z","comment":"G11"},{"logid":150000001,"type":"delete",
+            "action":"delete","actionhidden":true,"userhidden":true,"commenthidden":true,
+            "timestamp":"2023-05-01T12:01:00Z"}]}}"#,
+        )
+        .unwrap();
+        let events = &resp.query.logevents;
+        assert_eq!(events[0].kind, "delete");
+        assert_eq!(events[0].comment, "G11");
+        assert_eq!(events[1].title, "");
+    }
+
+    #[tokio::test]
+    async fn test_new_pages()
+    {
+        let pages = WikiClient::new()
+            .new_pages(&NewPagesOptions::new().limit(5))
+            .await
+            .unwrap();
+        assert_eq!(pages.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_resume_new_pages()
+    {
+        let client = WikiClient::new();
+        let options = NewPagesOptions::new().limit(5);
+        let first = client.new_pages_paged(&options).await.unwrap();
+        let second = client
+            .resume_new_pages(&options, first.cont.as_ref().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(second.items.len(), 5);
+        assert!(second.items[0].timestamp <= first.items[4].timestamp);
+    }
+
+    #[tokio::test]
+    async fn test_log_events()
+    {
+        let events = WikiClient::new()
+            .log_events(&LogEventsOptions::new().log_type("delete").limit(5))
+            .await
+            .unwrap();
+        assert!(events.iter().all(|e| e.kind == "delete"));
+    }
+
+    #[test]
+    fn test_parse_most_read()
+    {
+        let resp: FeaturedResponse = serde_json::from_str(
+            r#"{"tfa":{"title":"Example"},"mostread":{"date":"2023-04-30Z","articles":[{"views":1234567,
+            "rank":3,"view_history":[{"date":"2023-04-30Z","views":1234567}],"type":"standard",
+            "title":"Albert_Einstein","displaytitle":"<span>Albert Einstein</span>",
+            "namespace":{"id":0,"text":""},"pageid":736,"normalizedtitle":"Albert Einstein",
+            "description":"German-born theoretical physicist (1879–1955)","content_urls":{"desktop":{
+            "page":"https://en.wikipedia.org/wiki/Albert_Einstein"},"mobile":{
+            "page":"https://en.m.wikipedia.org/wiki/Albert_Einstein"}}}]}}"#,
+        )
+        .unwrap();
+        let articles = to_most_read("2023/05/01", resp).unwrap();
+        assert_eq!(articles.len(), 1);
+        assert_eq!(articles[0].title, "Albert Einstein");
+        assert_eq!((articles[0].rank, articles[0].views), (3, 1234567));
+        assert_eq!(articles[0].url, "https://en.wikipedia.org/wiki/Albert_Einstein");
+
+        let err = to_most_read("2023/05/01", serde_json::from_str(r#"{"tfa":{}}"#).unwrap());
+        assert_eq!(
+            err,
+            Err(WikiError::PageNotFoundError(
+                "most read articles of 2023/05/01".to_string()
+            ))
+        );
+        assert_eq!(
+            feed_date(&parse_timestamp("2023-05-01T12:00:00Z").unwrap()),
+            "2023/05/01"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_most_read()
+    {
+        let date = parse_timestamp("2023-05-01T00:00:00Z").unwrap();
+        let articles = WikiClient::new().most_read(&date, "de").await.unwrap();
+        assert!(!articles.is_empty());
+        assert!(articles.windows(2).all(|x| x[0].rank < x[1].rank));
+    }
+}
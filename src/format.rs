@@ -0,0 +1,128 @@
+//! Markdown and HTML cards for [`PageOverview`]s, for chat bots that post the
+//! same fields every time.
+//!
+//! Only available with the `format` feature.
+
+use crate::{truncate, PageOverview};
+
+/// The number of characters of the extract shown on a card
+const CARD_EXTRACT_CHARS: usize = 300;
+
+impl PageOverview
+{
+    /// Format the page as a Markdown card: the title linking to the page,
+    /// followed by the start of the extract as a quote.
+    ///
+    /// The output sticks to the Markdown subset Discord and Matrix both
+    /// render.
+    pub fn to_markdown_card(&self) -> String
+    {
+        let mut card = format!("**[{}]({})**", escape_markdown(&self.title), self.url);
+
+        let extract = truncate(&self.extract, CARD_EXTRACT_CHARS);
+        for line in extract.lines().filter(|x| !x.trim().is_empty()) {
+            card.push_str("\n> ");
+            card.push_str(&escape_markdown(line));
+        }
+
+        card
+    }
+
+    /// Format the page as an HTML card: the thumbnail if there is one, the
+    /// title linking to the page and the start of the extract.
+    pub fn to_html_card(&self) -> String
+    {
+        let mut card = String::from("<div class=\"wiki-card\">");
+
+        if let Some(thumbnail) = &self.thumbnail {
+            card.push_str(&format!(
+                "<img src=\"{}\" width=\"{}\" height=\"{}\" alt=\"{}\">",
+                escape_html(&thumbnail.source),
+                thumbnail.width,
+                thumbnail.height,
+                escape_html(&self.title),
+            ));
+        }
+
+        card.push_str(&format!(
+            "<a href=\"{}\"><strong>{}</strong></a><p>{}</p></div>",
+            escape_html(&self.url),
+            escape_html(&self.title),
+            escape_html(&truncate(&self.extract, CARD_EXTRACT_CHARS)),
+        ));
+
+        card
+    }
+}
+
+/// Escape the characters Markdown would otherwise interpret
+fn escape_markdown(s: &str) -> String
+{
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(
+            c,
+            '\\' | '*' | '_' | '~' | '`' | '|' | '[' | ']' | '(' | ')' | '<' | '>' | '#'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escape the characters HTML would otherwise interpret
+fn escape_html(s: &str) -> String
+{
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{PageOverview, Thumbnail};
+
+    fn overview() -> PageOverview
+    {
+        PageOverview {
+            title: "C++ (programming language)".to_string(),
+            url: "https://en.wikipedia.org/wiki/C%2B%2B".to_string(),
+            extract: "C++ is a *general-purpose* programming language.\n\nIt was created by Bjarne Stroustrup."
+                .to_string(),
+            thumbnail: Some(Thumbnail {
+                source: "https://upload.wikimedia.org/cpp.png".to_string(),
+                width:  320,
+                height: 360,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_markdown_card()
+    {
+        assert_eq!(
+            overview().to_markdown_card(),
+            "**[C++ \\(programming language\\)](https://en.wikipedia.org/wiki/C%2B%2B)**\n> C++ is a \
+             \\*general-purpose\\* programming language.\n> It was created by Bjarne Stroustrup."
+        );
+    }
+
+    #[test]
+    fn test_html_card()
+    {
+        let mut overview = overview();
+        overview.title = "<b>\"Quotes\" & more</b>".to_string();
+        overview.thumbnail = None;
+        overview.extract = "Short.".to_string();
+        assert_eq!(
+            overview.to_html_card(),
+            "<div class=\"wiki-card\"><a href=\"https://en.wikipedia.org/wiki/C%2B%2B\"><strong>&lt;b&gt;&quot;\
+             Quotes&quot; &amp; more&lt;/b&gt;</strong></a><p>Short.</p></div>"
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! Geographic search for pages near a point.
+
+use futures::{stream, Stream};
+use serde::{Deserialize, Serialize};
+
+use crate::{PageId, WikiClient, WikiError};
+
+/// The largest search radius the API allows, in meters
+const MAX_RADIUS: u32 = 10_000;
+
+/// The largest number of results the API returns for one point
+const MAX_LIMIT: usize = 500;
+
+/// How many more pages [`WikiClient::nearby_stream`] asks for each time it runs
+/// out
+const STREAM_PAGE_SIZE: usize = 50;
+
+/// A page about something near the searched point.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NearbyPage
+{
+    /// Title of the page
+    pub title: String,
+
+    /// Id of the page
+    pub pageid: PageId,
+
+    /// Latitude of the subject in degrees
+    pub lat: f64,
+
+    /// Longitude of the subject in degrees
+    pub lon: f64,
+
+    /// Distance from the searched point in meters
+    pub dist: f64,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct GeoSearchQuery
+{
+    pub geosearch: Vec<NearbyPage>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct GeoSearchResponse
+{
+    pub query: GeoSearchQuery,
+}
+
+impl WikiClient
+{
+    /// Get up to `limit` pages within `radius` meters of a point, closest
+    /// first. The API allows a radius of up to 10km and up to 500 results.
+    pub async fn nearby(&self, lat: f64, lon: f64, radius: u32, limit: usize) -> Result<Vec<NearbyPage>, WikiError>
+    {
+        let resp: GeoSearchResponse = self
+            .get_json(&[
+                ("action", "query"),
+                ("format", "json"),
+                ("list", "geosearch"),
+                ("gscoord", &format!("{lat}|{lon}")),
+                ("gsradius", &radius.clamp(10, MAX_RADIUS).to_string()),
+                ("gslimit", &limit.clamp(1, MAX_LIMIT).to_string()),
+                ("gsnamespace", "0"),
+                ("formatversion", "2"),
+            ])
+            .await?;
+
+        Ok(resp.query.geosearch)
+    }
+
+    /// Stream the pages within 10km of a point, closest first, fetching more
+    /// as the stream is consumed.
+    ///
+    /// The geosearch API has no continuation, so each batch re-requests the
+    /// point with a larger limit and skips the pages already yielded. The
+    /// stream ends after 500 pages, the most the API returns for one point.
+    pub fn nearby_stream(&self, lat: f64, lon: f64) -> impl Stream<Item = Result<NearbyPage, WikiError>>
+    {
+        struct State
+        {
+            client:  WikiClient,
+            buffer:  std::vec::IntoIter<NearbyPage>,
+            yielded: usize,
+            done:    bool,
+        }
+
+        let state = State {
+            client:  self.clone(),
+            buffer:  Vec::new().into_iter(),
+            yielded: 0,
+            done:    false,
+        };
+
+        stream::unfold(state, move |mut state| {
+            async move {
+                loop {
+                    if let Some(page) = state.buffer.next() {
+                        state.yielded += 1;
+                        return Some((Ok(page), state));
+                    }
+                    if state.done {
+                        return None;
+                    }
+
+                    let limit = (state.yielded + STREAM_PAGE_SIZE).min(MAX_LIMIT);
+                    match state.client.nearby(lat, lon, MAX_RADIUS, limit).await {
+                        Ok(pages) => {
+                            state.done = pages.len() < limit || limit == MAX_LIMIT;
+                            state.buffer = next_batch(pages, state.yielded).into_iter();
+                        }
+                        Err(e) => {
+                            state.done = true;
+                            return Some((Err(e), state));
+                        }
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Skip the `yielded` closest pages of a re-requested, distance ordered batch
+fn next_batch(mut pages: Vec<NearbyPage>, yielded: usize) -> Vec<NearbyPage>
+{
+    pages.sort_by(|a, b| a.dist.total_cmp(&b.dist));
+    pages.into_iter().skip(yielded).collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use futures::StreamExt;
+
+    use super::{next_batch, GeoSearchResponse};
+    use crate::WikiClient;
+
+    #[test]
+    fn test_parse_geosearch()
+    {
+        let resp: GeoSearchResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"geosearch":[{"pageid":18618509,"ns":0,
+            "title":"Wikimedia Foundation","lat":37.78785,"lon":-122.39966,"dist":18.8,"primary":true},
+            {"pageid":42936625,"ns":0,"title":"Foxcroft Building","lat":37.78779,"lon":-122.39947,
+            "dist":3.2,"primary":true}]}}"#,
+        )
+        .unwrap();
+        let pages = next_batch(resp.query.geosearch, 1);
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].title, "Wikimedia Foundation");
+        assert_eq!(pages[0].dist, 18.8);
+    }
+
+    #[tokio::test]
+    async fn test_nearby_stream()
+    {
+        let pages: Vec<_> = WikiClient::new()
+            .nearby_stream(37.786971, -122.399677)
+            .take(60)
+            .collect()
+            .await;
+        assert_eq!(pages.len(), 60);
+        let pages: Vec<_> = pages.into_iter().map(Result::unwrap).collect();
+        assert!(pages.windows(2).all(|w| w[0].dist <= w[1].dist));
+    }
+}
@@ -0,0 +1,412 @@
+//! Link graphs around a seed page, e.g. for knowledge-graph visualizations.
+
+use std::collections::{HashMap, HashSet};
+
+use futures::{stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::Continue,
+    types::{deserialize_flag, deserialize_pages, resolve_title, TitleMapping},
+    WikiClient, WikiError,
+};
+
+/// The number of titles whose links are requested at once
+const TITLES_PER_REQUEST: usize = 50;
+
+/// The number of link requests in flight at once
+const CONCURRENCY: usize = 4;
+
+/// The article links of pages, by title
+type Links = HashMap<String, Vec<String>>;
+
+/// The titles of the pages the API returned for requested titles, by
+/// requested title
+type Resolved = HashMap<String, String>;
+
+/// A page in a [`LinkGraph`].
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphNode
+{
+    /// Title of the page
+    pub title: String,
+
+    /// The number of links between the seed page and this page
+    pub depth: usize,
+}
+
+/// A link from one page of a [`LinkGraph`] to another, as indices into
+/// [`LinkGraph::nodes`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GraphEdge
+{
+    pub from: usize,
+    pub to:   usize,
+}
+
+/// The pages reachable from a seed page through internal links, and the
+/// links between them.
+///
+/// Links aren't resolved through redirects: a link to a redirect is a node
+/// titled like the redirect, with the links of the page it redirects to, and
+/// separate from the node of that page if it's linked as well.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkGraph
+{
+    /// The pages, the seed page first, in the order they were discovered
+    pub nodes: Vec<GraphNode>,
+
+    /// The links between the pages
+    pub edges: Vec<GraphEdge>,
+}
+
+impl LinkGraph
+{
+    /// Serialize the graph to the Graphviz DOT language
+    pub fn to_dot(&self) -> String
+    {
+        let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+
+        let mut dot = String::from("digraph links {\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            dot.push_str(&format!("    {i} [label=\"{}\"];\n", escape(&node.title)));
+        }
+        for edge in &self.edges {
+            dot.push_str(&format!("    {} -> {};\n", edge.from, edge.to));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Serialize the graph to GraphML, with the title and depth of each page
+    /// as node data
+    pub fn to_graphml(&self) -> String
+    {
+        let escape = |s: &str| {
+            s.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
+        };
+
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"title\" for=\"node\" attr.name=\"title\" attr.type=\"string\"/>\n",
+            "  <key id=\"depth\" for=\"node\" attr.name=\"depth\" attr.type=\"int\"/>\n",
+            "  <graph id=\"links\" edgedefault=\"directed\">\n",
+        ));
+        for (i, node) in self.nodes.iter().enumerate() {
+            xml.push_str(&format!(
+                "    <node id=\"n{i}\"><data key=\"title\">{}</data><data key=\"depth\">{}</data></node>\n",
+                escape(&node.title),
+                node.depth,
+            ));
+        }
+        for edge in &self.edges {
+            xml.push_str(&format!(
+                "    <edge source=\"n{}\" target=\"n{}\"/>\n",
+                edge.from, edge.to
+            ));
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+
+    /// Add the links found from the pages of the frontier at `depth`, adding
+    /// newly found pages while there are fewer than `limit`. Returns the newly
+    /// found pages, which make up the next frontier.
+    fn extend(&mut self, frontier: &[String], links: &Links, depth: usize, limit: usize) -> Vec<String>
+    {
+        let mut index: HashMap<String, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (x.title.clone(), i))
+            .collect();
+        let mut edges: HashSet<GraphEdge> = self.edges.iter().copied().collect();
+        let mut next = Vec::new();
+
+        for from_title in frontier {
+            let (Some(&from), Some(targets)) = (index.get(from_title), links.get(from_title))
+            else {
+                continue;
+            };
+
+            for target in targets {
+                let to = match index.get(target) {
+                    Some(&to) => to,
+                    None if self.nodes.len() < limit => {
+                        self.nodes.push(GraphNode {
+                            title: target.clone(),
+                            depth: depth + 1,
+                        });
+                        index.insert(target.clone(), self.nodes.len() - 1);
+                        next.push(target.clone());
+                        self.nodes.len() - 1
+                    }
+                    None => continue,
+                };
+
+                let edge = GraphEdge { from, to };
+                if from != to && edges.insert(edge) {
+                    self.edges.push(edge);
+                }
+            }
+        }
+
+        next
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RLink
+{
+    pub title: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RLinksPage
+{
+    pub title:   String,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub missing: bool,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub invalid: bool,
+    #[serde(default)]
+    pub links:   Vec<RLink>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LinksQuery
+{
+    #[serde(default)]
+    pub normalized: Vec<TitleMapping>,
+    #[serde(default)]
+    pub redirects:  Vec<TitleMapping>,
+    #[serde(default, deserialize_with = "deserialize_pages")]
+    pub pages:      Vec<RLinksPage>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LinksResponse
+{
+    #[serde(rename = "continue")]
+    pub(crate) cont: Option<Continue>,
+    #[serde(default)]
+    pub query:       LinksQuery,
+}
+
+impl WikiClient
+{
+    /// Crawl the internal links of `seed` breadth-first, up to `depth` links
+    /// away and `limit` pages in total, and return the pages and the links
+    /// between them. Fails with [`WikiError::PageNotFoundError`] if `seed`
+    /// doesn't exist.
+    ///
+    /// Links are requested for up to 50 pages at once, with up to 4 requests
+    /// in flight.
+    pub async fn link_graph(&self, seed: &str, depth: usize, limit: usize) -> Result<LinkGraph, WikiError>
+    {
+        let mut graph = LinkGraph::default();
+        if limit == 0 {
+            return Ok(graph);
+        }
+
+        // Resolve the seed first, so a redirect like "USA" doesn't end up as a
+        // node of its own.
+        let (resolved, _) = self.links_of(&[seed.to_string()]).await?;
+        let Some(seed) = resolved.get(seed).cloned()
+        else {
+            return Err(WikiError::PageNotFoundError(seed.to_string()));
+        };
+
+        graph.nodes.push(GraphNode {
+            title: seed.clone(),
+            depth: 0,
+        });
+
+        let mut frontier = vec![seed];
+        for d in 0..depth {
+            if frontier.is_empty() {
+                break;
+            }
+
+            let batches: Vec<(Resolved, Links)> =
+                stream::iter(frontier.chunks(TITLES_PER_REQUEST).map(|x| self.links_of(x)))
+                    .buffer_unordered(CONCURRENCY)
+                    .try_collect()
+                    .await?;
+
+            let links = batches.into_iter().flat_map(|(_, links)| links).collect();
+            frontier = graph.extend(&frontier, &links, d, limit);
+        }
+
+        Ok(graph)
+    }
+
+    /// Get the article links of each of `titles`, following continuations.
+    /// Returns the resolved title of each of `titles` that exists, and the
+    /// links by requested title.
+    async fn links_of(&self, titles: &[String]) -> Result<(Resolved, Links), WikiError>
+    {
+        let responses: Vec<LinksResponse> = self
+            .get_json_continued(
+                &[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("prop", "links"),
+                    ("titles", &titles.join("|")),
+                    ("plnamespace", "0"),
+                    ("pllimit", "max"),
+                    ("redirects", "1"),
+                    ("formatversion", "2"),
+                ],
+                |x: &LinksResponse| x.cont.clone(),
+            )
+            .await?;
+
+        Ok(merge_links(titles, responses))
+    }
+}
+
+/// Merge the links of continued responses and key them by requested title,
+/// so pages reached through a redirect or an unnormalized title still get
+/// their links. Titles of pages that don't exist are left out.
+fn merge_links(titles: &[String], responses: Vec<LinksResponse>) -> (Resolved, Links)
+{
+    let mut mappings = Vec::new();
+    let mut links = Links::new();
+
+    for resp in responses {
+        mappings.extend(resp.query.normalized);
+        mappings.extend(resp.query.redirects);
+        for page in resp.query.pages.into_iter().filter(|x| !x.missing && !x.invalid) {
+            links
+                .entry(page.title)
+                .or_default()
+                .extend(page.links.into_iter().map(|x| x.title));
+        }
+    }
+
+    let mut resolved = Resolved::new();
+    let mut requested = Links::new();
+    for title in titles {
+        let to = resolve_title(&mappings, title);
+        if let Some(targets) = links.get(&to) {
+            requested.insert(title.clone(), targets.clone());
+            resolved.insert(title.clone(), to);
+        }
+    }
+
+    (resolved, requested)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::collections::HashMap;
+
+    use super::{merge_links, GraphEdge, LinkGraph, LinksResponse};
+    use crate::WikiClient;
+
+    #[test]
+    fn test_merge_continued_links()
+    {
+        let first: LinksResponse = serde_json::from_str(
+            r#"{"continue":{"plcontinue":"3434750|0|Canada","continue":"||"},"query":{
+            "redirects":[{"from":"USA","to":"United States"}],
+            "pages":[{"pageid":3434750,"ns":0,"title":"United States","links":[{"ns":0,"title":"Alaska"}]}]}}"#,
+        )
+        .unwrap();
+        let second: LinksResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"pages":[{"pageid":3434750,"ns":0,"title":"United States",
+            "links":[{"ns":0,"title":"Canada"}]}]}}"#,
+        )
+        .unwrap();
+
+        let (resolved, links) = merge_links(&["USA".to_string()], vec![first, second]);
+        assert_eq!(resolved["USA"], "United States");
+        assert_eq!(links["USA"], ["Alaska", "Canada"]);
+    }
+
+    #[test]
+    fn test_merge_links_leaves_out_missing_pages()
+    {
+        let resp: LinksResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"pages":[{"ns":0,"title":"No such page","missing":true}]}}"#,
+        )
+        .unwrap();
+        let (resolved, links) = merge_links(&["No such page".to_string()], vec![resp]);
+        assert!(resolved.is_empty());
+        assert!(links.is_empty());
+    }
+
+    #[test]
+    fn test_extend_from_redirected_frontier()
+    {
+        let resp: LinksResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"normalized":[{"from":"paris","to":"Paris"}],
+            "redirects":[{"from":"USA","to":"United States"}],"pages":[{"pageid":3434750,"ns":0,
+            "title":"United States","links":[{"ns":0,"title":"Canada"}]},{"pageid":22989,"ns":0,"title":"Paris",
+            "links":[{"ns":0,"title":"France"}]}]}}"#,
+        )
+        .unwrap();
+        let frontier = ["USA".to_string(), "paris".to_string()];
+        let (_, links) = merge_links(&frontier, vec![resp]);
+
+        let mut graph = LinkGraph::default();
+        for title in ["Seed", "USA", "paris"] {
+            graph.nodes.push(super::GraphNode {
+                title: title.to_string(),
+                depth: 1,
+            });
+        }
+        let next = graph.extend(&frontier, &links, 1, 10);
+        assert_eq!(next, ["Canada", "France"]);
+        assert_eq!(
+            graph.edges,
+            [GraphEdge { from: 1, to: 3 }, GraphEdge { from: 2, to: 4 }]
+        );
+    }
+
+    #[test]
+    fn test_extend_respects_limit()
+    {
+        let mut graph = LinkGraph::default();
+        graph.nodes.push(super::GraphNode {
+            title: "A".to_string(),
+            depth: 0,
+        });
+
+        let links = HashMap::from([("A".to_string(), vec!["B".to_string(), "C".to_string(), "A".to_string()])]);
+        let next = graph.extend(&["A".to_string()], &links, 0, 2);
+        assert_eq!(next, ["B"]);
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges, [GraphEdge { from: 0, to: 1 }]);
+
+        let links = HashMap::from([("B".to_string(), vec!["A".to_string(), "C".to_string()])]);
+        let next = graph.extend(&next, &links, 1, 2);
+        assert!(next.is_empty());
+        assert_eq!(
+            graph.edges,
+            [GraphEdge { from: 0, to: 1 }, GraphEdge { from: 1, to: 0 }]
+        );
+
+        assert_eq!(
+            graph.to_dot(),
+            "digraph links {\n    0 [label=\"A\"];\n    1 [label=\"B\"];\n    0 -> 1;\n    1 -> 0;\n}\n"
+        );
+        assert!(graph
+            .to_graphml()
+            .contains("<node id=\"n1\"><data key=\"title\">B</data><data key=\"depth\">1</data></node>"));
+    }
+
+    #[tokio::test]
+    async fn test_link_graph()
+    {
+        let graph = WikiClient::new().link_graph("USA", 2, 30).await.unwrap();
+        assert_eq!(graph.nodes[0].title, "United States");
+        assert_eq!(graph.nodes.len(), 30);
+    }
+}
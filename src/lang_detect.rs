@@ -0,0 +1,151 @@
+//! Routing search terms to the Wikipedia of the language they're written in,
+//! for multilingual assistant bots.
+//!
+//! Only available with the `lang-detect` feature.
+
+use crate::{Page, SearchOptions, WikiClient, WikiError};
+
+/// The confidence below which a detected language is ignored. Short search
+/// terms rarely reach `whatlang`'s own reliability threshold, but names like
+/// "Albert Einstein" score well below this.
+const MIN_CONFIDENCE: f64 = 0.3;
+
+/// Detect the language `text` is written in, as a Wikipedia language code
+pub fn detect_language(text: &str) -> Option<&'static str>
+{
+    let info = whatlang::detect(text)?;
+    if info.confidence() < MIN_CONFIDENCE {
+        return None;
+    }
+
+    wiki_language(info.lang())
+}
+
+/// The Wikipedia language code matching a language `whatlang` detects
+fn wiki_language(lang: whatlang::Lang) -> Option<&'static str>
+{
+    let code = match lang.code() {
+        "afr" => "af",
+        "aka" => "ak",
+        "amh" => "am",
+        "ara" => "ar",
+        "aze" => "az",
+        "bel" => "be",
+        "ben" => "bn",
+        "bul" => "bg",
+        "cat" => "ca",
+        "ces" => "cs",
+        "cmn" => "zh",
+        "cym" => "cy",
+        "dan" => "da",
+        "deu" => "de",
+        "ell" => "el",
+        "eng" => "en",
+        "epo" => "eo",
+        "est" => "et",
+        "fin" => "fi",
+        "fra" => "fr",
+        "guj" => "gu",
+        "heb" => "he",
+        "hin" => "hi",
+        "hrv" => "hr",
+        "hun" => "hu",
+        "hye" => "hy",
+        "ind" => "id",
+        "ita" => "it",
+        "jav" => "jv",
+        "jpn" => "ja",
+        "kan" => "kn",
+        "kat" => "ka",
+        "khm" => "km",
+        "kor" => "ko",
+        "lat" => "la",
+        "lav" => "lv",
+        "lit" => "lt",
+        "mal" => "ml",
+        "mar" => "mr",
+        "mkd" => "mk",
+        "mya" => "my",
+        "nep" => "ne",
+        "nld" => "nl",
+        "nob" => "no",
+        "ori" => "or",
+        "pan" => "pa",
+        "pes" => "fa",
+        "pol" => "pl",
+        "por" => "pt",
+        "ron" => "ro",
+        "rus" => "ru",
+        "sin" => "si",
+        "slk" => "sk",
+        "slv" => "sl",
+        "sna" => "sn",
+        "spa" => "es",
+        "srp" => "sr",
+        "swe" => "sv",
+        "tam" => "ta",
+        "tel" => "te",
+        "tgl" => "tl",
+        "tha" => "th",
+        "tuk" => "tk",
+        "tur" => "tr",
+        "ukr" => "uk",
+        "urd" => "ur",
+        "uzb" => "uz",
+        "vie" => "vi",
+        "yid" => "yi",
+        "zul" => "zu",
+        _ => return None,
+    };
+
+    Some(code)
+}
+
+impl WikiClient
+{
+    /// Search for `search_term` on the Wikipedia of the language it's written
+    /// in, e.g. es.wikipedia.org for "teoría de la relatividad". If the
+    /// language can't be detected confidently, or nothing is found there, the
+    /// search falls back to this client's own language. [`Page::lang`] tells
+    /// which Wikipedia the page was found on.
+    pub async fn resolve_auto(&self, search_term: &str) -> Result<Page, WikiError>
+    {
+        let options = SearchOptions::default();
+
+        if let Some(lang) = detect_language(search_term).filter(|x| *x != self.language()) {
+            match self.clone().with_language(lang).search(search_term, &options).await {
+                Ok(mut pages) => return Ok(pages.swap_remove(0)),
+                Err(WikiError::PageNotFoundError(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(self.search(search_term, &options).await?.swap_remove(0))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::detect_language;
+    use crate::WikiClient;
+
+    #[test]
+    fn test_detect_language()
+    {
+        assert_eq!(detect_language("teoría de la relatividad"), Some("es"));
+        assert_eq!(detect_language("théorie de la relativité"), Some("fr"));
+        assert_eq!(detect_language("東京タワー"), Some("ja"));
+        assert_eq!(detect_language("Albert Einstein"), None);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_auto()
+    {
+        let page = WikiClient::new()
+            .resolve_auto("teoría de la relatividad")
+            .await
+            .unwrap();
+        assert_eq!(&*page.lang, "es");
+    }
+}
@@ -0,0 +1,305 @@
+//! Links to the same subject on the Wikipedias of other languages, and
+//! summaries that fall back to other languages through them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::Continue,
+    types::{deserialize_flag, deserialize_pages, resolve_title, TitleMapping},
+    AuthSession, LangLink, SummaryOptions, WikiClient, WikiError,
+};
+
+/// A summary from the first of several languages that had one, see
+/// [`WikiClient::summary_with_fallback`].
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct LocalizedSummary
+{
+    /// Language code of the Wikipedia the summary is from
+    pub lang: String,
+
+    /// Title of the page the summary is of, on that Wikipedia
+    pub title: String,
+
+    /// The summary
+    pub summary: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RLangLinksPage
+{
+    #[serde(default)]
+    pub title:     String,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub missing:   bool,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub invalid:   bool,
+    #[serde(default)]
+    pub langlinks: Vec<LangLink>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LangLinksQuery
+{
+    #[serde(default)]
+    pub normalized: Vec<TitleMapping>,
+    #[serde(default)]
+    pub redirects:  Vec<TitleMapping>,
+    #[serde(default, deserialize_with = "deserialize_pages")]
+    pub pages:      Vec<RLangLinksPage>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LangLinksResponse
+{
+    #[serde(rename = "continue")]
+    pub(crate) cont: Option<Continue>,
+    #[serde(default)]
+    pub query:       LangLinksQuery,
+}
+
+impl WikiClient
+{
+    /// Get the links from the page titled `title` to pages on the same subject
+    /// on the Wikipedias of other languages, with the names of the languages in
+    /// the client's language
+    pub async fn lang_links(&self, title: &str) -> Result<Vec<LangLink>, WikiError>
+    {
+        let responses: Vec<LangLinksResponse> = self
+            .get_json_continued(
+                &[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("prop", "langlinks"),
+                    ("titles", title),
+                    ("llprop", "url|langname|autonym"),
+                    ("llinlanguagecode", self.language()),
+                    ("lllimit", "max"),
+                    ("redirects", "1"),
+                    ("formatversion", "2"),
+                ],
+                |x: &LangLinksResponse| x.cont.clone(),
+            )
+            .await?;
+
+        to_lang_links(title, responses)
+    }
+
+    /// Get a summary of the page titled `title` using `options`, in the first
+    /// language of `langs` it has a non-empty summary in, e.g. `["pt", "es",
+    /// "en"]`. `title` is the title on the client's Wikipedia. The pages in
+    /// other languages are found through its language links, which are only
+    /// requested if a language other than the client's is tried. Returns the
+    /// language that was used along with the summary.
+    ///
+    /// Fails with [`WikiError::PageNotFoundError`] if none of the languages has
+    /// a summary. Other errors end the search right away.
+    pub async fn summary_with_fallback(
+        &self,
+        title: &str,
+        langs: &[&str],
+        options: &SummaryOptions,
+    ) -> Result<LocalizedSummary, WikiError>
+    {
+        let mut links: Option<Vec<LangLink>> = None;
+
+        for &lang in langs {
+            let title = if lang == self.language() {
+                title.to_string()
+            }
+            else {
+                if links.is_none() {
+                    links = Some(match self.lang_links(title).await {
+                        Ok(x) => x,
+                        Err(WikiError::PageNotFoundError(_)) => Vec::new(),
+                        Err(e) => return Err(e),
+                    });
+                }
+                match links.iter().flatten().find(|x| x.lang == lang) {
+                    Some(link) => link.title.clone(),
+                    None => continue,
+                }
+            };
+
+            let client = self.clone().with_language(lang);
+            match client.summary_with_options(&title, options).await {
+                Ok(summary) if !summary.trim().is_empty() => {
+                    return Ok(LocalizedSummary {
+                        lang: lang.to_string(),
+                        title,
+                        summary,
+                    });
+                }
+                Ok(_) | Err(WikiError::PageNotFoundError(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(WikiError::PageNotFoundError(title.to_string()))
+    }
+
+    /// Get the language links of each of the pages titled `titles`, in batched
+    /// requests of 50 titles each, or 500 if `session` is a [bot
+    /// session](AuthSession::bot). The links are returned along with the title
+    /// they were requested for, in the order of `titles`; titles that don't
+    /// exist are left out.
+    pub async fn lang_links_many(
+        &self,
+        titles: &[&str],
+        session: Option<&AuthSession>,
+    ) -> Result<Vec<(String, Vec<LangLink>)>, WikiError>
+    {
+        let responses: Vec<LangLinksResponse> = self
+            .get_json_batched(
+                &[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("prop", "langlinks"),
+                    ("llprop", "url|langname|autonym"),
+                    ("llinlanguagecode", self.language()),
+                    ("lllimit", "max"),
+                    ("redirects", "1"),
+                    ("formatversion", "2"),
+                ],
+                titles,
+                session,
+                |x: &LangLinksResponse| x.cont.clone(),
+            )
+            .await?;
+
+        Ok(merge_lang_links(titles, responses))
+    }
+}
+
+/// Collect the language links of the one page of continued responses
+fn to_lang_links(title: &str, responses: Vec<LangLinksResponse>) -> Result<Vec<LangLink>, WikiError>
+{
+    let mut links = Vec::new();
+    for page in responses.into_iter().flat_map(|x| x.query.pages) {
+        if page.missing || page.invalid {
+            return Err(WikiError::PageNotFoundError(title.to_string()));
+        }
+        links.extend(page.langlinks);
+    }
+    Ok(links)
+}
+
+/// Merge the language links of continued and batched responses and key them
+/// by requested title, in the order of `titles`
+fn merge_lang_links(titles: &[&str], responses: Vec<LangLinksResponse>) -> Vec<(String, Vec<LangLink>)>
+{
+    let mut mappings = Vec::new();
+    let mut links: Vec<(String, Vec<LangLink>)> = Vec::new();
+
+    for resp in responses {
+        mappings.extend(resp.query.normalized);
+        mappings.extend(resp.query.redirects);
+
+        for page in resp.query.pages.into_iter().filter(|x| !x.missing && !x.invalid) {
+            match links.iter_mut().find(|(title, _)| *title == page.title) {
+                Some((_, x)) => x.extend(page.langlinks),
+                None => links.push((page.title, page.langlinks)),
+            }
+        }
+    }
+
+    let mut requested: Vec<(String, Vec<LangLink>)> = Vec::with_capacity(links.len());
+    for title in titles {
+        if requested.iter().any(|(x, _)| x == title) {
+            continue;
+        }
+        let to = resolve_title(&mappings, title);
+        if let Some((_, x)) = links.iter().find(|(title, _)| *title == to) {
+            requested.push((title.to_string(), x.clone()));
+        }
+    }
+
+    requested
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{merge_lang_links, to_lang_links, LangLinksResponse};
+    use crate::{SummaryOptions, WikiClient, WikiError};
+
+    #[test]
+    fn test_parse_lang_links()
+    {
+        let resp: LangLinksResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"pages":[{"pageid":736,"ns":0,"title":"Albert Einstein",
+            "langlinks":[{"lang":"de","url":"https://de.wikipedia.org/wiki/Albert_Einstein","langname":"German",
+            "autonym":"Deutsch","title":"Albert Einstein"},{"lang":"ja",
+            "url":"https://ja.wikipedia.org/wiki/%E3%82%A2%E3%83%AB%E3%83%99%E3%83%AB%E3%83%88%E3%83%BB%E3%82%A2%E3%82%A4%E3%83%B3%E3%82%B7%E3%83%A5%E3%82%BF%E3%82%A4%E3%83%B3",
+            "langname":"Japanese","autonym":"日本語","title":"アルベルト・アインシュタイン"}]}]}}"#,
+        )
+        .unwrap();
+
+        let links = to_lang_links("Albert Einstein", vec![resp]).unwrap();
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[1].lang, "ja");
+        assert_eq!(links[1].title, "アルベルト・アインシュタイン");
+        assert_eq!(links[1].autonym, "日本語");
+    }
+
+    #[test]
+    fn test_merge_batched_lang_links()
+    {
+        let first: LangLinksResponse = serde_json::from_str(
+            r#"{"continue":{"llcontinue":"736|ja","continue":"||"},"query":{
+            "normalized":[{"fromencoded":false,"from":"albert Einstein","to":"Albert Einstein"}],
+            "pages":[{"pageid":736,"ns":0,"title":"Albert Einstein","langlinks":[{"lang":"de",
+            "title":"Albert Einstein"}]},{"ns":0,"title":"Nowhere at all","missing":true}]}}"#,
+        )
+        .unwrap();
+        let second: LangLinksResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"pages":[{"pageid":736,"ns":0,"title":"Albert Einstein",
+            "langlinks":[{"lang":"ja","title":"アルベルト・アインシュタイン"}]}]}}"#,
+        )
+        .unwrap();
+        let third: LangLinksResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"redirects":[{"from":"Curie","to":"Marie Curie"}],
+            "pages":[{"pageid":20408,"ns":0,"title":"Marie Curie","langlinks":[{"lang":"pl",
+            "title":"Maria Skłodowska-Curie"}]}]}}"#,
+        )
+        .unwrap();
+
+        let links = merge_lang_links(
+            &["Curie", "Nowhere at all", "albert Einstein"],
+            vec![first, second, third],
+        );
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].0, "Curie");
+        assert_eq!(links[0].1[0].title, "Maria Skłodowska-Curie");
+        assert_eq!(links[1].0, "albert Einstein");
+        assert_eq!(links[1].1.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_summary_with_fallback()
+    {
+        let client = WikiClient::new();
+        let summary = client
+            .summary_with_fallback(
+                "Albert Einstein",
+                &["xx-nonexistent", "de", "en"],
+                &SummaryOptions::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(summary.lang, "de");
+        assert_eq!(summary.title, "Albert Einstein");
+        assert!(!summary.summary.is_empty());
+
+        let err = client
+            .summary_with_fallback("Nowhere at all 12345", &["de", "en"], &SummaryOptions::new())
+            .await;
+        assert!(matches!(err, Err(WikiError::PageNotFoundError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_lang_links()
+    {
+        let links = WikiClient::new().lang_links("Albert Einstein").await.unwrap();
+        assert!(links.iter().any(|x| x.lang == "de" && x.title == "Albert Einstein"));
+    }
+}
@@ -1,9 +1,85 @@
 //! Wikipedia api crate
 
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
+use client::{extra_params_setter, with_extra_params};
 use log::{error, info};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use serde::{Deserialize, Serialize};
+use types::{deserialize_flag, deserialize_pages, resolve_title, TitleMapping};
+
+pub mod audio;
+pub mod auth;
+mod cache;
+pub mod categories;
+pub mod client;
+pub mod commons;
+pub mod decode;
+pub mod export;
+pub mod feeds;
+#[cfg(feature = "format")]
+pub mod format;
+pub mod geo;
+pub mod graph;
+#[cfg(feature = "lang-detect")]
+pub mod lang_detect;
+pub mod langlinks;
+pub mod logging;
+pub mod math;
+pub mod news;
+pub mod notices;
+#[cfg(feature = "ores")]
+pub mod ores;
+pub mod overview;
+pub mod quotes;
+pub mod resolve;
+pub mod revisions;
+pub mod search;
+pub mod section;
+#[cfg(feature = "tower")]
+pub mod service;
+pub mod shorturl;
+pub mod stats;
+#[cfg(feature = "store")]
+pub mod store;
+pub mod title;
+pub mod types;
+pub mod watcher;
+pub mod wikidata;
+pub mod wiktionary;
+
+pub use audio::{AudioFile, AudioKind};
+pub use auth::AuthSession;
+pub use categories::CategoryNode;
+pub use client::{default_client, Continue, Paged, WikiClient, WikiClientBuilder};
+pub use commons::{License, MediaHit, MediaType};
+pub use decode::ResponseFormat;
+pub use export::{export, export_async, ExportFormat, ExportRecord};
+pub use feeds::{LogEvent, LogEventsOptions, MostReadArticle, NewPage, NewPagesOptions};
+pub use geo::NearbyPage;
+pub use graph::{GraphEdge, GraphNode, LinkGraph};
+pub use langlinks::LocalizedSummary;
+pub use logging::RequestLogging;
+pub use math::Formula;
+pub use news::NewsArticle;
+pub use notices::{Hatnote, MaintenanceKind, MaintenanceTag};
+#[cfg(feature = "ores")]
+pub use ores::{Score, ScoreModel};
+pub use overview::{PageOverview, PagePreview};
+pub use quotes::Quote;
+pub use resolve::{FallbackPolicy, Resolution, ResolveAttempt, ResolveStep};
+pub use revisions::RevisionContent;
+pub use search::{rerank, FullTextSearchOptions, RelevanceProfile, SnippetFormat};
+#[cfg(feature = "tower")]
+pub use service::{WikiRequest, WikiResponse};
+pub use stats::ClientStats;
+#[cfg(feature = "store")]
+pub use store::Store;
+pub use types::{
+    Category, Coordinates, ImageInfo, LangLink, PageId, Protection, Revision, SearchHit, Snippet, Thumbnail, Timestamp,
+};
+pub use watcher::{PageChange, WatchOptions};
+pub use wiktionary::{Definition, PartOfSpeech};
 
 #[derive(Clone, PartialEq, PartialOrd, Ord, Eq, Debug)]
 pub enum WikiError
@@ -11,7 +87,8 @@ pub enum WikiError
     /// The searched page wasn't found. The search term is stored in `String`
     PageNotFoundError(String),
 
-    /// Making a wikipedia request failed
+    /// Sending a request to Wikipedia failed, e.g. because the connection was
+    /// refused or reset
     PageRequestError,
 
     /// Error parsing the JSON
@@ -19,6 +96,67 @@ pub enum WikiError
 
     /// An error with the Wikipedia api response
     ResponseError,
+
+    /// A strict search matched several pages without any of them matching the
+    /// search term exactly. Holds the search term and the candidate titles.
+    AmbiguousResult(String, Vec<String>),
+
+    /// The request timed out
+    TimeoutError,
+
+    /// Wikipedia responded with an HTTP error status, which is stored in `u16`
+    HttpStatusError(u16),
+
+    /// A response didn't have the structure the API documents, e.g. because the
+    /// API changed
+    UnexpectedResponseShape
+    {
+        /// The structure the response should have had
+        expected: String,
+
+        /// The start of the offending JSON
+        got: String,
+    },
+
+    /// The response body was larger than the limit set with
+    /// [`WikiClientBuilder::max_response_size`], which is stored in `usize`
+    ResponseTooLarge(usize),
+
+    /// Writing exported results failed. The I/O error is described in
+    /// `String`.
+    WriteError(String),
+
+    /// Reading from or writing to a [`store`](crate::store) failed. The
+    /// SQLite error is described in `String`.
+    StoreError(String),
+
+    /// A [`WikiClientBuilder`] was given invalid settings, or a request URL
+    /// built from them isn't valid. The problem is described in `String`.
+    InvalidConfigError(String),
+}
+
+impl WikiError
+{
+    /// Whether the failed request may succeed if it's made again later, e.g.
+    /// after a timeout or when Wikipedia is rate limiting (429) or unavailable
+    /// (503). Errors caused by the request itself or by parsing the response,
+    /// like a page that doesn't exist, aren't retryable.
+    pub fn is_retryable(&self) -> bool
+    {
+        match self {
+            Self::PageRequestError | Self::TimeoutError => true,
+            Self::HttpStatusError(status) => matches!(status, 408 | 429 | 500 | 502 | 503 | 504),
+            Self::PageNotFoundError(_)
+            | Self::JsonParseError
+            | Self::ResponseError
+            | Self::AmbiguousResult(..)
+            | Self::UnexpectedResponseShape { .. }
+            | Self::ResponseTooLarge(_)
+            | Self::WriteError(_)
+            | Self::StoreError(_)
+            | Self::InvalidConfigError(_) => false,
+        }
+    }
 }
 
 impl std::fmt::Display for WikiError
@@ -42,6 +180,30 @@ impl std::fmt::Display for WikiError
                 error!("{m}");
                 m.to_string()
             }
+            Self::AmbiguousResult(e, candidates) => {
+                format!("AmbiguousResult: '{e}' could refer to {}.", candidates.join(", "))
+            }
+            Self::TimeoutError => {
+                let m = "TimeoutError: The request to Wikipedia timed out.";
+                error!("{m}");
+                m.to_string()
+            }
+            Self::HttpStatusError(status) => {
+                let m = format!("HttpStatusError: Wikipedia responded with status {status}.");
+                error!("{m}");
+                m
+            }
+            Self::UnexpectedResponseShape { expected, got } => {
+                let m = format!("UnexpectedResponseShape: Expected {expected}, got {got}.");
+                error!("{m}");
+                m
+            }
+            Self::ResponseTooLarge(limit) => {
+                format!("ResponseTooLarge: The response was larger than the limit of {limit} bytes.")
+            }
+            Self::WriteError(e) => format!("WriteError: Couldn't write the export: {e}."),
+            Self::StoreError(e) => format!("StoreError: {e}."),
+            Self::InvalidConfigError(e) => format!("InvalidConfigError: {e}."),
         };
 
         write!(f, "{m}")
@@ -50,30 +212,226 @@ impl std::fmt::Display for WikiError
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct RPage
+pub(crate) struct RPage
 {
-    pub pageid:  i64,
-    pub ns:      i64,
-    pub title:   String,
-    pub extract: String,
+    #[serde(default)]
+    pub pageid:    i64,
+    #[serde(default)]
+    pub ns:        i64,
+    #[serde(default)]
+    pub title:     String,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub missing:   bool,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub invalid:   bool,
+    #[serde(default)]
+    pub extract:   String,
+    #[serde(default)]
+    pub lastrevid: i64,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct Query
+pub(crate) struct Query
 {
+    #[serde(default, deserialize_with = "deserialize_pages")]
     pub pages: Vec<RPage>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct SummaryResponse
+pub(crate) struct SummaryResponse
 {
+    #[serde(default, deserialize_with = "deserialize_flag")]
     pub batchcomplete: bool,
+    #[serde(default)]
     pub query:         Query,
 }
 
-#[derive(Clone, PartialEq, PartialOrd, Ord, Eq, Debug)]
+/// An error reported by the action API in the response body.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ApiError
+{
+    pub code: String,
+    #[serde(default)]
+    pub info: String,
+}
+
+/// The codes of `action=parse` errors meaning that the page or revision to
+/// parse doesn't exist, or may not be seen
+const PARSE_NOT_FOUND_CODES: &[&str] = &["missingtitle", "nosuchrevid", "permissiondenied"];
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RParse
+{
+    pub title:    String,
+    #[serde(default)]
+    pub text:     String,
+    #[serde(default)]
+    pub wikitext: String,
+    #[serde(default)]
+    pub revid:    i64,
+    #[serde(default)]
+    pub sections: Vec<section::RSection>,
+}
+
+/// The response of any `action=parse` request, with the parts asked for with
+/// `prop` filled in.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ParseResponse
+{
+    pub parse: Option<RParse>,
+    pub error: Option<ApiError>,
+}
+
+impl ParseResponse
+{
+    /// The parsed page, failing with [`WikiError::PageNotFoundError`] for
+    /// `target` (a title, or a description of a revision) if it doesn't
+    /// exist
+    pub(crate) fn into_parse(self, target: &str) -> Result<RParse, WikiError>
+    {
+        match (self.parse, self.error) {
+            (Some(x), _) => Ok(x),
+            (None, Some(e)) if PARSE_NOT_FOUND_CODES.contains(&e.code.as_str()) => {
+                Err(WikiError::PageNotFoundError(target.to_string()))
+            }
+            (None, _) => Err(WikiError::ResponseError),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InfoActions
+{
+    #[serde(default)]
+    pub edit: bool,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InfoPage
+{
+    #[serde(default)]
+    pub title:        String,
+    pub pageid:       Option<PageId>,
+    #[serde(default)]
+    pub canonicalurl: String,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub missing:      bool,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub invalid:      bool,
+    pub touched:      Option<Timestamp>,
+    #[serde(default)]
+    pub lastrevid:    i64,
+    #[serde(default)]
+    pub protection:   Vec<Protection>,
+    #[serde(default)]
+    pub actions:      InfoActions,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InfoQuery
+{
+    #[serde(default)]
+    pub normalized: Vec<TitleMapping>,
+    #[serde(default)]
+    pub redirects:  Vec<TitleMapping>,
+    #[serde(default, deserialize_with = "deserialize_pages")]
+    pub pages:      Vec<InfoPage>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InfoResponse
+{
+    #[serde(default)]
+    pub query: InfoQuery,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct DescriptionPage
+{
+    #[serde(default)]
+    pub title:       String,
+    pub description: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct DescriptionsQuery
+{
+    #[serde(default)]
+    pub normalized: Vec<TitleMapping>,
+    #[serde(default)]
+    pub redirects:  Vec<TitleMapping>,
+    #[serde(default, deserialize_with = "deserialize_pages")]
+    pub pages:      Vec<DescriptionPage>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct DescriptionsResponse
+{
+    #[serde(rename = "continue")]
+    pub(crate) cont: Option<Continue>,
+    #[serde(default)]
+    pub query:       DescriptionsQuery,
+}
+
+/// How a request identifies a page: by title, or by id, which survives
+/// renames
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum PageRef<'a>
+{
+    Title(&'a str),
+    Id(PageId),
+}
+
+impl PageRef<'_>
+{
+    /// The parameter naming the page in `action=query` requests
+    pub(crate) fn query_param(&self) -> (&'static str, String)
+    {
+        match self {
+            Self::Title(x) => ("titles", x.to_string()),
+            Self::Id(x) => ("pageids", x.to_string()),
+        }
+    }
+
+    /// The parameter naming the page in `action=parse` requests
+    pub(crate) fn parse_param(&self) -> (&'static str, String)
+    {
+        match self {
+            Self::Title(x) => ("page", x.to_string()),
+            Self::Id(x) => ("pageid", x.to_string()),
+        }
+    }
+
+    /// The key summaries of the page are cached under. Ids can't collide with
+    /// titles, which can't contain `#`.
+    fn cache_key(&self) -> String
+    {
+        match self {
+            Self::Title(x) => x.to_string(),
+            Self::Id(x) => format!("#{x}"),
+        }
+    }
+}
+
+/// Titles as they are and ids as `page 736`, for errors
+impl std::fmt::Display for PageRef<'_>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self {
+            Self::Title(x) => write!(f, "{x}"),
+            Self::Id(x) => write!(f, "page {x}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
 /// The result of a search operation.
 pub struct Page
 {
@@ -82,103 +440,946 @@ pub struct Page
 
     /// The URL of the page
     pub url: Arc<str>,
+
+    /// Language code of the Wikipedia the page is on, e.g. `en`
+    pub lang: Arc<str>,
+
+    /// The short description of the page, e.g. "German-born theoretical
+    /// physicist (1879–1955)", if it has one and it was returned with the page.
+    /// See [`SearchOptions::descriptions`].
+    pub description: Option<String>,
+
+    /// Id of the page, if it's known. Requests for the page are then made by
+    /// id, so they still find it after it's renamed.
+    pub pageid: Option<PageId>,
+
+    /// The client the page was found with, whose connection pool and cache
+    /// the methods of the page share
+    client: Option<WikiClient>,
+}
+
+/// Pages are compared by title, URL and language only, not by the client they
+/// were found with
+impl PartialEq for Page
+{
+    fn eq(&self, other: &Self) -> bool { self.key() == other.key() }
+}
+
+impl Eq for Page {}
+
+impl PartialOrd for Page
+{
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for Page
+{
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering { self.key().cmp(&other.key()) }
+}
+
+/// Options for [`Page::search_with_options`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SearchOptions
+{
+    limit:             usize,
+    namespace:         i64,
+    strict:            bool,
+    resolve_redirects: bool,
+    descriptions:      bool,
+    extra_params:      Vec<(String, String)>,
+}
+
+impl Default for SearchOptions
+{
+    fn default() -> Self
+    {
+        Self {
+            limit:             1,
+            namespace:         0,
+            strict:            false,
+            resolve_redirects: false,
+            descriptions:      false,
+            extra_params:      Vec::new(),
+        }
+    }
+}
+
+impl SearchOptions
+{
+    extra_params_setter!();
+
+    /// Create `SearchOptions` with the defaults: one result from the main
+    /// (article) namespace, without strict matching.
+    pub fn new() -> Self { Self::default() }
+
+    /// The maximum number of results to return. Values below 1 are treated as
+    /// 1.
+    pub fn limit(mut self, limit: usize) -> Self
+    {
+        self.limit = limit.max(1);
+        self
+    }
+
+    /// The namespace to search in, `0` being articles.
+    pub fn namespace(mut self, namespace: i64) -> Self
+    {
+        self.namespace = namespace;
+        self
+    }
+
+    /// Fail with [`WikiError::AmbiguousResult`] instead of picking the best
+    /// match when several pages match and none of them matches the search term
+    /// exactly.
+    pub fn strict(mut self, strict: bool) -> Self
+    {
+        self.strict = strict;
+        self
+    }
+
+    /// Return the pages redirects point to instead of the redirects
+    /// themselves. A redirect to a section of a page keeps the section as the
+    /// fragment of [`Page::url`], see [`Page::anchor`].
+    pub fn resolve_redirects(mut self, resolve_redirects: bool) -> Self
+    {
+        self.resolve_redirects = resolve_redirects;
+        self
+    }
+
+    /// Fill in the [`Page::description`] of each result in a second request.
+    /// The opensearch API returns descriptions only on wikis that store them
+    /// locally, and the Wikipedias get theirs from Wikidata, so without this
+    /// most results have none.
+    pub fn descriptions(mut self, descriptions: bool) -> Self
+    {
+        self.descriptions = descriptions;
+        self
+    }
+}
+
+/// The format summaries are returned in.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum ExtractFormat
+{
+    /// Plain text
+    #[default]
+    Plain,
+
+    /// The rendered HTML of the lead section as shown on Wikipedia, including
+    /// infoboxes and images. Not cut to [`SummaryOptions::chars`].
+    Html,
+
+    /// HTML limited to basic formatting like paragraphs, bold, italics and
+    /// lists
+    LimitedHtml,
+}
+
+/// Options for [`WikiClient::summary_with_options`].
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct SummaryOptions
+{
+    format:       ExtractFormat,
+    chars:        usize,
+    intro_only:   bool,
+    extra_params: Vec<(String, String)>,
+}
+
+impl Default for SummaryOptions
+{
+    fn default() -> Self
+    {
+        Self {
+            format:       ExtractFormat::default(),
+            chars:        1000,
+            intro_only:   false,
+            extra_params: Vec::new(),
+        }
+    }
+}
+
+impl SummaryOptions
+{
+    extra_params_setter!();
+
+    /// Create `SummaryOptions` with the defaults: up to 1000 characters of
+    /// plain text.
+    pub fn new() -> Self { Self::default() }
+
+    /// The format of the summary
+    pub fn format(mut self, format: ExtractFormat) -> Self
+    {
+        self.format = format;
+        self
+    }
+
+    /// The number of characters to cut the summary to, between 1 and 1200.
+    pub fn chars(mut self, chars: usize) -> Self
+    {
+        self.chars = chars.clamp(1, 1200);
+        self
+    }
+
+    /// Return the whole lead section, the text before the first heading,
+    /// instead of cutting the summary to [`Self::chars`]. This gives cleaner
+    /// previews, since the summary ends where a paragraph does.
+    pub fn intro_only(mut self, intro_only: bool) -> Self
+    {
+        self.intro_only = intro_only;
+        self
+    }
+}
+
+/// The result of [`WikiClient::summary_if_changed`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SummaryStatus
+{
+    /// The page hasn't been edited since the given revision
+    NotModified,
+
+    /// The page has been edited since the given revision, or no revision was
+    /// given
+    Modified
+    {
+        summary: String,
+
+        /// Id of the revision the summary is of, to pass as `prev_revid` next
+        /// time
+        revid: i64,
+    },
+}
+
+/// How a search term was resolved to a page title.
+#[derive(Clone, PartialEq, PartialOrd, Ord, Eq, Debug)]
+pub struct SearchOutcome
+{
+    /// The search term as given by the caller
+    pub query: Arc<str>,
+
+    /// The title Wikipedia matched the search term to
+    pub matched_title: Arc<str>,
+
+    /// Whether the matched title differs from the search term, e.g. because a
+    /// misspelling was corrected. Differences in case and whitespace alone
+    /// don't count as a correction.
+    pub corrected: bool,
+}
+
+impl SearchOutcome
+{
+    /// Create a new `SearchOutcome`, working out whether `matched_title` is a
+    /// correction of `query`.
+    pub fn new(query: &str, matched_title: &str) -> Self
+    {
+        let simplify = |s: &str| {
+            s.replace('_', " ")
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase()
+        };
+
+        Self {
+            query:         Arc::from(query),
+            matched_title: Arc::from(matched_title),
+            corrected:     simplify(query) != simplify(matched_title),
+        }
+    }
 }
 
 impl Page
 {
-    /// Create a new `Page`
+    /// Create a new `Page` on the English Wikipedia. If `title` points to a
+    /// section, like `Article#Section`, the section is kept as the fragment of
+    /// the URL.
     pub fn new(title: String, url: String) -> Self
     {
+        let (title, url) = match title.split_once('#') {
+            Some((title, anchor)) if !url.contains('#') => {
+                (title.to_string(), format!("{url}#{}", encode_anchor(anchor)))
+            }
+            Some((title, _)) => (title.to_string(), url),
+            None => (title, url),
+        };
+
         Self {
-            title: Arc::from(title),
-            url:   Arc::from(url),
+            title:       Arc::from(title),
+            url:         Arc::from(url),
+            lang:        Arc::from("en"),
+            description: None,
+            pageid:      None,
+            client:      None,
+        }
+    }
+
+    /// Set the language code of the Wikipedia the page is on
+    pub fn with_language(mut self, lang: &str) -> Self
+    {
+        self.lang = Arc::from(lang);
+        self.client = self.client.map(|x| x.with_language(lang));
+        self
+    }
+
+    /// Make the methods of the page use `client`, and so share its connection
+    /// pool, settings and cache. The page is then on the Wikipedia of the
+    /// client's language. Pages returned by [`WikiClient::search`] already
+    /// use the client that found them.
+    pub fn with_client(mut self, client: &WikiClient) -> Self
+    {
+        self.lang = Arc::from(client.language());
+        self.client = Some(client.clone());
+        self
+    }
+
+    /// Set the id of the page, so requests for it are made by id
+    pub fn with_pageid(mut self, pageid: PageId) -> Self
+    {
+        self.pageid = Some(pageid);
+        self
+    }
+
+    /// Look up the page with the id `pageid` on the English Wikipedia with
+    /// the [`default_client`]. See [`WikiClient::page_by_id`].
+    pub async fn from_pageid(pageid: PageId) -> Result<Self, WikiError> { default_client().page_by_id(pageid).await }
+
+    fn key(&self) -> (&str, &str, &str) { (&self.title, &self.url, &self.lang) }
+
+    /// How requests identify the page: by id if it's known, else by title
+    fn page_ref(&self) -> PageRef<'_>
+    {
+        match self.pageid {
+            Some(x) => PageRef::Id(x),
+            None => PageRef::Title(&self.title),
         }
     }
 
-    /// Search for a page on Wikipedia and return a `Page`
+    /// The URL of the page, including the section it points to, if any
+    pub fn url(&self) -> &str { &self.url }
+
+    /// The heading of the section the page points to, e.g. `History` for
+    /// `Article#History`, if the page was found through a link or redirect to
+    /// a section
+    pub fn anchor(&self) -> Option<String>
+    {
+        let (_, anchor) = self.url.split_once('#')?;
+        let anchor = percent_decode_str(anchor).decode_utf8_lossy().replace('_', " ");
+        (!anchor.is_empty()).then_some(anchor)
+    }
+
+    /// The canonical `https://xx.wikipedia.org/wiki/Title` URL of the page,
+    /// built from the title and language rather than taken from the API, so it
+    /// carries no tracking parameters.
+    pub fn canonical_url(&self) -> String
+    {
+        format!("https://{}.wikipedia.org/wiki/{}", self.lang, encode_title(&self.title))
+    }
+
+    /// The URL of the page on the mobile site, `https://xx.m.wikipedia.org`
+    pub fn mobile_url(&self) -> String
+    {
+        format!(
+            "https://{}.m.wikipedia.org/wiki/{}",
+            self.lang,
+            encode_title(&self.title)
+        )
+    }
+
+    /// Search for a page on the English Wikipedia with the [`default_client`]
+    /// and return a `Page`
     pub async fn search(search_term: &str) -> Result<Self, WikiError>
     {
-        type SearchResult = (String, Vec<String>, Vec<String>, Vec<String>);
+        Ok(Self::search_with_outcome(search_term).await?.0)
+    }
 
-        // Replace spaces with %20 for the url
-        let title = search_term.replace(' ', "%20");
+    /// Search for a page on Wikipedia and return a `Page` along with a
+    /// [`SearchOutcome`] describing how the search term was matched, so callers
+    /// can show "showing results for…" when it was corrected.
+    pub async fn search_with_outcome(search_term: &str) -> Result<(Self, SearchOutcome), WikiError>
+    {
+        let page = Self::search_with_options(search_term, &SearchOptions::default())
+            .await?
+            .swap_remove(0);
 
-        let request_url = format!(
-            "https://en.wikipedia.org/w/api.php?action=opensearch&search={}&limit=1&namespace=0&format=json",
-            title.trim()
-        );
-        let page;
+        let outcome = SearchOutcome::new(search_term.trim(), &page.title);
+        if outcome.corrected {
+            info!("Search for '{}' matched '{}'", outcome.query, outcome.matched_title);
+        }
+
+        Ok((page, outcome))
+    }
+
+    /// Search for pages on Wikipedia using `options` and return every match,
+    /// best match first. The returned list is never empty.
+    pub async fn search_with_options(search_term: &str, options: &SearchOptions) -> Result<Vec<Self>, WikiError>
+    {
+        default_client().search(search_term, options).await
+    }
+
+    pub async fn get_summary(self) -> Result<String, WikiError>
+    {
+        self.get_summary_with_options(&SummaryOptions::default()).await
+    }
+
+    /// Get a summary of the page using `options`, e.g. to get it as HTML
+    pub async fn get_summary_with_options(&self, options: &SummaryOptions) -> Result<String, WikiError>
+    {
+        self.client().summary_of(self.page_ref(), options).await
+    }
+
+    /// Get a preview of the page for link unfurling
+    pub async fn preview(&self) -> Result<PagePreview, WikiError> { self.client().preview(&self.title).await }
+
+    /// Get the audio files linked on the page, like spoken versions of the
+    /// article and pronunciations of its title
+    pub async fn audio_files(&self) -> Result<Vec<AudioFile>, WikiError>
+    {
+        self.client().audio_files(&self.title).await
+    }
+
+    /// Get the math formulas on the page, with their TeX source and the URLs
+    /// of their rendered images
+    pub async fn get_formulas(&self) -> Result<Vec<Formula>, WikiError> { self.client().formulas(&self.title).await }
+
+    /// Get the hatnotes on the page, like `This article is about…` or `For
+    /// other uses, see…`. See [`WikiClient::hatnotes`].
+    pub async fn get_hatnotes(&self) -> Result<Vec<Hatnote>, WikiError> { self.client().hatnotes(&self.title).await }
+
+    /// Get the maintenance templates on the page, like cleanup banners, stub
+    /// notices and `[citation needed]`. See [`WikiClient::maintenance_tags`].
+    pub async fn maintenance_tags(&self) -> Result<Vec<MaintenanceTag>, WikiError>
+    {
+        self.client().maintenance_tags(&self.title).await
+    }
+
+    /// Get the plain text of the section titled `name`, cut to at most `chars`
+    /// characters. See [`WikiClient::summarize_section`].
+    pub async fn summarize_section(&self, name: &str, chars: usize) -> Result<String, WikiError>
+    {
+        self.client().summarize_section(&self.title, name, chars).await
+    }
+
+    /// Get the plain text of the section the page points to, see
+    /// [`Self::anchor`], cut to at most `chars` characters. Pages that don't
+    /// point to a section get the summary of the lead section instead.
+    pub async fn summarize_anchor(&self, chars: usize) -> Result<String, WikiError>
+    {
+        match self.anchor() {
+            Some(anchor) => self.summarize_section(&anchor, chars).await,
+            None => self.get_summary_with_options(&SummaryOptions::new().chars(chars)).await,
+        }
+    }
+
+    /// Get the wikitext of the page as of revision `revid`. See
+    /// [`WikiClient::revision_content`]. Fails with
+    /// [`WikiError::PageNotFoundError`] if the revision is of another page.
+    pub async fn content_at(&self, revid: i64) -> Result<RevisionContent, WikiError>
+    {
+        self.client().revision_content_of(self.page_ref(), revid).await
+    }
+
+    /// Get the revision of the page that was current at `timestamp`, e.g. to
+    /// show the page as of a date with [`Self::content_at`]
+    pub async fn revision_at(&self, timestamp: &Timestamp) -> Result<Revision, WikiError>
+    {
+        self.client().revision_of_at(self.page_ref(), timestamp).await
+    }
+
+    /// Get the categories the page belongs to
+    pub async fn categories(&self) -> Result<Vec<Category>, WikiError> { self.client().categories(&self.title).await }
+
+    /// Get the links to pages on the same subject on the Wikipedias of other
+    /// languages
+    pub async fn lang_links(&self) -> Result<Vec<LangLink>, WikiError> { self.client().lang_links(&self.title).await }
+
+    /// Get the protection levels currently applied to the page
+    pub async fn protection(&self) -> Result<Vec<Protection>, WikiError> { Ok(self.get_info().await?.protection) }
+
+    /// Check whether the page can currently be edited by the user of
+    /// `session`, or by an anonymous editor if it's `None`, so bots can skip
+    /// protected pages instead of failing on write.
+    pub async fn can_edit(&self, session: Option<&AuthSession>) -> Result<bool, WikiError>
+    {
+        Ok(self.client().info_as(self.page_ref(), session).await?.actions.edit)
+    }
+
+    /// The client the page was found with, or the [`default_client`] for the
+    /// Wikipedia the page is on
+    fn client(&self) -> WikiClient
+    {
+        match &self.client {
+            Some(x) => x.clone(),
+            None => default_client().as_ref().clone().with_language(&self.lang),
+        }
+    }
+
+    async fn get_info(&self) -> Result<InfoPage, WikiError> { self.client().info(self.page_ref()).await }
+
+    /// Get a plain text summary of the page, unless it hasn't been edited since
+    /// revision `prev_revid`, so periodic refreshers only download summaries
+    /// that changed.
+    ///
+    /// Summaries are cached by the client the page was found with (see
+    /// [`Self::with_client`]), if it was built with a
+    /// [cache TTL](crate::WikiClientBuilder::cache_ttl).
+    pub async fn get_summary_if_changed(&self, prev_revid: Option<i64>) -> Result<SummaryStatus, WikiError>
+    {
+        self.client().summary_of_if_changed(self.page_ref(), prev_revid).await
+    }
+}
+
+/// Characters left as they are in the path of a `/wiki/` URL, matching what
+/// MediaWiki itself produces
+pub(crate) const TITLE_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b';')
+    .remove(b'@')
+    .remove(b'$')
+    .remove(b'!')
+    .remove(b'*')
+    .remove(b'(')
+    .remove(b')')
+    .remove(b',')
+    .remove(b'/')
+    .remove(b':');
+
+/// Encode a title for use in the path of a `/wiki/` URL
+pub(crate) fn encode_title(title: &str) -> String
+{
+    utf8_percent_encode(&title::normalize(title).replace(' ', "_"), TITLE_ENCODE_SET).to_string()
+}
+
+/// Encode a section heading for use as the fragment of a page URL
+pub(crate) fn encode_anchor(anchor: &str) -> String
+{
+    utf8_percent_encode(&anchor.trim().replace(' ', "_"), TITLE_ENCODE_SET).to_string()
+}
+
+/// Get the title, description and URL of each result out of an
+/// `action=opensearch` response, which is an array of the search term and three
+/// arrays of those
+fn parse_opensearch(resp: serde_json::Value) -> Result<Vec<(String, String, String)>, WikiError>
+{
+    let unexpected = |what: &serde_json::Value| {
+        let mut got = what.to_string();
+        if let Some((end, _)) = got.char_indices().nth(200) {
+            got = format!("{}…", &got[..end]);
+        }
+        WikiError::UnexpectedResponseShape {
+            expected: "an array of the search term and three arrays of strings".to_string(),
+            got,
+        }
+    };
+    let strings = |x: &serde_json::Value| -> Option<Vec<String>> {
+        x.as_array()?.iter().map(|x| x.as_str().map(str::to_string)).collect()
+    };
 
-        // Make the API call, parse the json to a `Page`.
-        if let Ok(resp) = {
-            match reqwest::get(&request_url).await {
-                Ok(x) => {
-                    info!("Requested '{}'", request_url);
-                    x
+    match resp.as_array().map(Vec::as_slice) {
+        Some([term, titles, descriptions, urls]) if term.is_string() => {
+            match (strings(titles), strings(descriptions), strings(urls)) {
+                (Some(titles), Some(descriptions), Some(urls))
+                    if titles.len() == urls.len() && titles.len() == descriptions.len() =>
+                {
+                    Ok(titles
+                        .into_iter()
+                        .zip(descriptions)
+                        .zip(urls)
+                        .map(|((t, d), u)| (t, d, u))
+                        .collect())
                 }
-                Err(_) => return Err(WikiError::PageRequestError),
+                _ => Err(unexpected(&resp)),
             }
-            .json::<SearchResult>()
-            .await
-        } {
-            let t = match resp.1.get(0) {
-                Some(x) => x.to_string(),
-                None => return Err(WikiError::PageNotFoundError(search_term.to_string())),
-            };
+        }
+        _ => Err(unexpected(&resp)),
+    }
+}
 
-            let u = match resp.3.get(0) {
-                Some(x) => x.to_string(),
-                None => return Err(WikiError::PageNotFoundError(search_term.to_string())),
-            };
+/// Cut `s` to at most `max` characters at a word boundary, adding an ellipsis
+/// if anything was cut
+pub(crate) fn truncate(s: &str, max: usize) -> String
+{
+    let s = s.trim();
+    match s.char_indices().nth(max) {
+        None => s.to_string(),
+        Some((end, _)) => {
+            let cut = &s[..end];
+            let cut = cut.rfind(char::is_whitespace).map_or(cut, |x| &cut[..x]);
+            format!("{}…", cut.trim_end())
+        }
+    }
+}
 
-            page = Self::new(t, u);
+impl WikiClient
+{
+    /// Search for pages on Wikipedia using `options` and return every match,
+    /// best match first. The returned list is never empty.
+    pub async fn search(&self, search_term: &str, options: &SearchOptions) -> Result<Vec<Page>, WikiError>
+    {
+        // Strict matching needs a runner-up to tell whether the top match is
+        // ambiguous.
+        let limit = if options.strict {
+            options.limit.max(2)
         }
         else {
-            return Err(WikiError::JsonParseError);
+            options.limit
+        };
+
+        let limit = limit.to_string();
+        let namespace = options.namespace.to_string();
+        let redirects = if options.resolve_redirects { "resolve" } else { "return" };
+        let extra_params: Vec<String> = options.extra_params.iter().map(|(k, v)| format!("{k}={v}")).collect();
+        // Everything that changes the result, with the term last since the key
+        // is normalized like a title
+        let miss_key = format!(
+            "{limit}|{namespace}|{redirects}|{}|{}",
+            extra_params.join("&"),
+            search_term.trim()
+        );
+        let wiki = self.api_url();
+        if self.cache().is_miss("search", &wiki, &miss_key) {
+            self.counters().record_cache_hit();
+            return Err(WikiError::PageNotFoundError(search_term.to_string()));
+        }
+
+        let params = with_extra_params(
+            &[
+                ("action", "opensearch"),
+                ("search", search_term.trim()),
+                ("limit", &limit),
+                ("namespace", &namespace),
+                ("redirects", redirects),
+                ("format", "json"),
+            ],
+            &options.extra_params,
+        );
+        let mut pages: Vec<Page> = parse_opensearch(self.get_json(&params).await?)?
+            .into_iter()
+            .map(|(t, d, u)| {
+                let mut page = Page::new(t, u).with_client(self);
+                page.description = Some(d).filter(|x| !x.is_empty());
+                page
+            })
+            .collect();
+
+        if pages.is_empty() {
+            self.cache().insert_miss("search", &wiki, &miss_key);
+            return Err(WikiError::PageNotFoundError(search_term.to_string()));
+        }
+
+        if options.strict && pages.len() > 1 && SearchOutcome::new(search_term.trim(), &pages[0].title).corrected {
+            return Err(WikiError::AmbiguousResult(
+                search_term.to_string(),
+                pages.iter().map(|p| p.title.to_string()).collect(),
+            ));
         }
-        Ok(page)
+
+        pages.truncate(options.limit);
+        if options.descriptions {
+            let titles: Vec<&str> = pages.iter().map(|x| &*x.title).collect();
+            let mut descriptions = self.descriptions(&titles).await?;
+            for page in pages.iter_mut().filter(|x| x.description.is_none()) {
+                page.description = descriptions.remove(&*page.title);
+            }
+        }
+        Ok(pages)
     }
 
-    pub async fn get_summary(self) -> Result<String, WikiError>
+    /// Get the short descriptions of the pages titled `titles`, by requested
+    /// title. Pages without a description are left out.
+    async fn descriptions(&self, titles: &[&str]) -> Result<HashMap<String, String>, WikiError>
     {
-        let request_url =
-        format!(
-            "https://en.wikipedia.org/w/api.php?action=query&format=json&prop=extracts&titles={}&formatversion=2&exchars=1000&explaintext=1&redirects=1",
-            self.title
-        );
+        let responses: Vec<DescriptionsResponse> = self
+            .get_json_batched(
+                &[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("prop", "description"),
+                    ("redirects", "1"),
+                    ("formatversion", "2"),
+                ],
+                titles,
+                None,
+                |x: &DescriptionsResponse| x.cont.clone(),
+            )
+            .await?;
 
-        // Make the API call, parse the json to a `Page`.
-        let resp = match {
-            match reqwest::get(&request_url).await {
-                Ok(x) => {
-                    info!("Requested '{}'", request_url);
-                    x
+        let mut mappings = Vec::new();
+        let mut descriptions = HashMap::new();
+        for resp in responses {
+            mappings.extend(resp.query.normalized);
+            mappings.extend(resp.query.redirects);
+            for page in resp.query.pages {
+                if let Some(description) = page.description {
+                    descriptions.insert(page.title, description);
                 }
-                Err(_) => return Err(WikiError::PageRequestError),
             }
-            .json::<SummaryResponse>()
-            .await
-        } {
+        }
+
+        Ok(titles
+            .iter()
+            .filter_map(|x| Some((x.to_string(), descriptions.get(&resolve_title(&mappings, x))?.clone())))
+            .collect())
+    }
+
+    /// Get a plain text summary of the page titled `title`
+    pub async fn summary(&self, title: &str) -> Result<String, WikiError>
+    {
+        self.summary_with_options(title, &SummaryOptions::default()).await
+    }
+
+    /// Get a summary of the page titled `title` using `options`
+    ///
+    /// If the client was built with a
+    /// [cache TTL](crate::WikiClientBuilder::cache_ttl), summaries fetched
+    /// within the TTL are served from the cache.
+    pub async fn summary_with_options(&self, title: &str, options: &SummaryOptions) -> Result<String, WikiError>
+    {
+        self.summary_of(PageRef::Title(title), options).await
+    }
+
+    /// Like [`Self::summary_with_options`], but for the page with the id
+    /// `pageid`
+    pub async fn summary_by_id(&self, pageid: PageId, options: &SummaryOptions) -> Result<String, WikiError>
+    {
+        self.summary_of(PageRef::Id(pageid), options).await
+    }
+
+    pub(crate) async fn summary_of(&self, page: PageRef<'_>, options: &SummaryOptions) -> Result<String, WikiError>
+    {
+        let key = page.cache_key();
+        let wiki = self.api_url();
+        if let Some(summary) = self.cache().fresh_summary(&wiki, &key, options) {
+            self.counters().record_cache_hit();
+            return Ok(summary);
+        }
+        if self.cache().is_miss("summary", &wiki, &key) {
+            self.counters().record_cache_hit();
+            return Err(WikiError::PageNotFoundError(page.to_string()));
+        }
+
+        let (summary, revid) = match self.fetch_summary(page, options).await {
             Ok(x) => x,
-            Err(_) => return Err(WikiError::JsonParseError),
+            Err(e @ WikiError::PageNotFoundError(_)) => {
+                self.cache().insert_miss("summary", &wiki, &key);
+                return Err(e);
+            }
+            Err(e) => return Err(e),
         };
+        self.cache().insert_summary(&wiki, &key, options, revid, &summary);
+        Ok(summary)
+    }
+
+    /// Get the page with the id `pageid`, e.g. one stored earlier, even if it
+    /// was renamed since. The returned page keeps the id, so requests made
+    /// through it are made by id too.
+    pub async fn page_by_id(&self, pageid: PageId) -> Result<Page, WikiError>
+    {
+        let info = self.info(PageRef::Id(pageid)).await?;
+        Ok(Page::new(info.title, info.canonicalurl)
+            .with_client(self)
+            .with_pageid(pageid))
+    }
+
+    /// Get a plain text summary of the page titled `title`, unless it hasn't
+    /// been edited since revision `prev_revid`.
+    ///
+    /// This first makes a lightweight request for the latest revision id. Only
+    /// if that differs from `prev_revid`, and the summary of that revision
+    /// isn't cached, is the summary itself downloaded.
+    pub async fn summary_if_changed(&self, title: &str, prev_revid: Option<i64>) -> Result<SummaryStatus, WikiError>
+    {
+        self.summary_of_if_changed(PageRef::Title(title), prev_revid).await
+    }
+
+    pub(crate) async fn summary_of_if_changed(
+        &self,
+        page: PageRef<'_>,
+        prev_revid: Option<i64>,
+    ) -> Result<SummaryStatus, WikiError>
+    {
+        let options = SummaryOptions::default();
+        let key = page.cache_key();
+        let wiki = self.api_url();
+
+        let revid = self.info(page).await?.lastrevid;
+        if prev_revid == Some(revid) {
+            return Ok(SummaryStatus::NotModified);
+        }
+
+        if let Some(summary) = self.cache().summary_at(&wiki, &key, &options, revid) {
+            self.counters().record_cache_hit();
+            return Ok(SummaryStatus::Modified { summary, revid });
+        }
+
+        let (summary, revid) = self.fetch_summary(page, &options).await?;
+        self.cache().insert_summary(&wiki, &key, &options, revid, &summary);
+        Ok(SummaryStatus::Modified { summary, revid })
+    }
+
+    /// Download a summary of `page`, along with the id of the revision it's of
+    async fn fetch_summary(&self, page: PageRef<'_>, options: &SummaryOptions) -> Result<(String, i64), WikiError>
+    {
+        if options.format == ExtractFormat::Html {
+            return self.lead_html(page, &options.extra_params).await;
+        }
+
+        let chars = options.chars.to_string();
+        let (key, value) = page.query_param();
+        let mut params = vec![
+            ("action", "query"),
+            ("format", "json"),
+            ("prop", "extracts|info"),
+            (key, &value),
+            ("formatversion", "2"),
+            ("redirects", "1"),
+        ];
+        if options.intro_only {
+            params.push(("exintro", "1"));
+        }
+        else {
+            params.push(("exchars", &chars));
+        }
+        if options.format == ExtractFormat::Plain {
+            params.push(("explaintext", "1"));
+        }
 
-        let summary_text = match resp.query.pages.get(0) {
+        let resp: SummaryResponse = self
+            .get_json(&with_extra_params(&params, &options.extra_params))
+            .await?;
+
+        let extract = match resp.query.pages.into_iter().next() {
+            Some(x) if x.missing || x.invalid => return Err(WikiError::PageNotFoundError(page.to_string())),
             Some(x) => x,
             None => return Err(WikiError::ResponseError),
+        };
+
+        Ok((extract.extract, extract.lastrevid))
+    }
+
+    /// Get the rendered HTML of the lead section of `page`, along with the id
+    /// of the revision it's of
+    async fn lead_html(&self, page: PageRef<'_>, extra_params: &[(String, String)])
+        -> Result<(String, i64), WikiError>
+    {
+        let (key, value) = page.parse_param();
+        let params = with_extra_params(
+            &[
+                ("action", "parse"),
+                ("format", "json"),
+                (key, &value),
+                ("prop", "text|revid"),
+                ("section", "0"),
+                ("disableeditsection", "1"),
+                ("disablelimitreport", "1"),
+                ("redirects", "1"),
+                ("formatversion", "2"),
+            ],
+            extra_params,
+        );
+        let resp: ParseResponse = self.get_json(&params).await?;
+        let parse = resp.into_parse(&page.to_string())?;
+        Ok((parse.text, parse.revid))
+    }
+
+    /// Get the `prop=info` details of `page`
+    pub(crate) async fn info(&self, page: PageRef<'_>) -> Result<InfoPage, WikiError> { self.info_as(page, None).await }
+
+    /// Get the `prop=info` details of `page`, with the actions tested for the
+    /// user of `session`
+    pub(crate) async fn info_as(&self, page: PageRef<'_>, session: Option<&AuthSession>)
+        -> Result<InfoPage, WikiError>
+    {
+        let (key, value) = page.query_param();
+        let resp: InfoResponse = self
+            .get_json_as(
+                &[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("prop", "info"),
+                    ("inprop", "protection|url"),
+                    ("intestactions", "edit"),
+                    (key, &value),
+                    ("formatversion", "2"),
+                    ("redirects", "1"),
+                ],
+                session,
+            )
+            .await?;
+
+        match resp.query.pages.into_iter().next() {
+            Some(x) if x.missing || x.invalid => Err(WikiError::PageNotFoundError(page.to_string())),
+            Some(x) => Ok(x),
+            None => Err(WikiError::ResponseError),
         }
-        .extract
-        .to_owned();
+    }
+
+    /// Get the protection levels currently applied to each of the pages titled
+    /// `titles`, in batched requests of 50 titles each, or 500 if `session` is
+    /// a [bot session](AuthSession::bot). The protections are returned along
+    /// with the title they were requested for, in the order of `titles`; titles
+    /// that don't exist are left out.
+    pub async fn protection_many(
+        &self,
+        titles: &[&str],
+        session: Option<&AuthSession>,
+    ) -> Result<Vec<(String, Vec<Protection>)>, WikiError>
+    {
+        let responses: Vec<InfoResponse> = self
+            .get_json_batched(
+                &[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("prop", "info"),
+                    ("inprop", "protection"),
+                    ("formatversion", "2"),
+                    ("redirects", "1"),
+                ],
+                titles,
+                session,
+                |_: &InfoResponse| None,
+            )
+            .await?;
 
-        Ok(summary_text)
+        Ok(merge_protections(titles, responses))
     }
 }
 
-#[cfg(test)]
+/// Merge the pages of batched responses and key their protections by requested
+/// title, in the order of `titles`
+fn merge_protections(titles: &[&str], responses: Vec<InfoResponse>) -> Vec<(String, Vec<Protection>)>
+{
+    let mut mappings = Vec::new();
+    let mut pages = Vec::new();
+    for resp in responses {
+        mappings.extend(resp.query.normalized);
+        mappings.extend(resp.query.redirects);
+        pages.extend(resp.query.pages.into_iter().filter(|x| !x.missing && !x.invalid));
+    }
+
+    let mut requested: Vec<(String, Vec<Protection>)> = Vec::with_capacity(pages.len());
+    for title in titles {
+        if requested.iter().any(|(x, _)| x == title) {
+            continue;
+        }
+        let to = resolve_title(&mappings, title);
+        if let Some(page) = pages.iter().find(|x| x.title == to) {
+            requested.push((title.to_string(), page.protection.clone()));
+        }
+    }
+
+    requested
+}
 
+#[cfg(test)]
 pub mod tests
 {
-    use super::{Page, WikiError};
+    use std::time::Duration;
+
+    use super::{
+        merge_protections, parse_opensearch, truncate, ExtractFormat, InfoResponse, LangLink, Page, PageId,
+        ParseResponse, SearchOptions, SearchOutcome, SummaryOptions, SummaryResponse, SummaryStatus, WikiClient,
+        WikiError,
+    };
 
     #[tokio::test]
     async fn test_search_page()
@@ -202,6 +1403,381 @@ pub mod tests
         assert_eq!(page, expected_page);
     }
 
+    #[tokio::test]
+    async fn test_search_page_outcome_corrected()
+    {
+        let (_, outcome) = Page::search_with_outcome("progrmming lang").await.unwrap();
+        assert_eq!(&*outcome.matched_title, "Programming language");
+        assert!(outcome.corrected);
+    }
+
+    #[tokio::test]
+    async fn test_search_page_descriptions()
+    {
+        let options = SearchOptions::new().descriptions(true);
+        let page = Page::search_with_options("Barack Obama", &options)
+            .await
+            .unwrap()
+            .swap_remove(0);
+        assert!(page.description.unwrap().contains("President of the United States"));
+    }
+
+    #[tokio::test]
+    async fn test_search_page_with_limit()
+    {
+        let pages = Page::search_with_options("Einstein", &SearchOptions::new().limit(5))
+            .await
+            .unwrap();
+        assert_eq!(pages.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_search_page_strict_ambiguous()
+    {
+        let err = Page::search_with_options("progrmming lang", &SearchOptions::new().strict(true)).await;
+        assert!(matches!(err, Err(WikiError::AmbiguousResult(..))));
+    }
+
+    #[tokio::test]
+    async fn test_get_page_summary_intro_only()
+    {
+        let client = WikiClient::new();
+        let options = SummaryOptions::new().intro_only(true);
+        let intro = client.summary_with_options("Albert Einstein", &options).await.unwrap();
+        assert!(!intro.contains("=="));
+        assert!(intro.chars().count() > 1000);
+    }
+
+    #[tokio::test]
+    async fn test_get_page_summary_html()
+    {
+        let page = Page::search("Albert Einstein").await.unwrap();
+        let options = SummaryOptions::new().format(ExtractFormat::LimitedHtml);
+        let r = page.get_summary_with_options(&options).await.unwrap();
+        assert!(r.starts_with('<'));
+    }
+
+    #[test]
+    fn test_page_urls()
+    {
+        let page = Page::new("C++".to_string(), String::new());
+        assert_eq!(page.canonical_url(), "https://en.wikipedia.org/wiki/C%2B%2B");
+
+        let page = Page::new("Zürich (canton)".to_string(), String::new()).with_language("de");
+        assert_eq!(
+            page.canonical_url(),
+            "https://de.wikipedia.org/wiki/Z%C3%BCrich_(canton)"
+        );
+        assert_eq!(
+            page.mobile_url(),
+            "https://de.m.wikipedia.org/wiki/Z%C3%BCrich_(canton)"
+        );
+
+        let page = Page::new("AT&T: What's next?".to_string(), String::new());
+        assert_eq!(
+            page.canonical_url(),
+            "https://en.wikipedia.org/wiki/AT%26T:_What%27s_next%3F"
+        );
+    }
+
+    #[test]
+    fn test_page_shares_client_cache()
+    {
+        let client = WikiClient::builder()
+            .cache_ttl(Duration::from_secs(60))
+            .language("de")
+            .build()
+            .unwrap();
+        let page = Page::new("Albert Einstein".to_string(), String::new()).with_client(&client);
+        assert_eq!(&*page.lang, "de");
+
+        client.cache().insert_summary(
+            &client.api_url(),
+            "Albert Einstein",
+            &SummaryOptions::new(),
+            42,
+            "Albert Einstein war...",
+        );
+        assert_eq!(
+            page.client()
+                .cache()
+                .summary_at(&page.client().api_url(), "Albert Einstein", &SummaryOptions::new(), 42)
+                .as_deref(),
+            Some("Albert Einstein war...")
+        );
+        assert_eq!(
+            page,
+            Page::new("Albert Einstein".to_string(), String::new()).with_language("de")
+        );
+    }
+
+    #[test]
+    fn test_parse_missing_parse_response()
+    {
+        let resp: ParseResponse = serde_json::from_str(
+            r#"{"error":{"code":"missingtitle","info":"The page you specified doesn't exist.","docref":"..."}}"#,
+        )
+        .unwrap();
+        assert!(resp.parse.is_none());
+        assert_eq!(
+            resp.into_parse("Nowhere at all"),
+            Err(WikiError::PageNotFoundError("Nowhere at all".to_string()))
+        );
+
+        let resp: ParseResponse =
+            serde_json::from_str(r#"{"error":{"code":"nosuchrevid","info":"There is no revision with ID 1."}}"#)
+                .unwrap();
+        assert_eq!(
+            resp.into_parse("revision 1"),
+            Err(WikiError::PageNotFoundError("revision 1".to_string()))
+        );
+
+        let resp: ParseResponse = serde_json::from_str(r#"{"error":{"code":"internal_api_error"}}"#).unwrap();
+        assert_eq!(resp.into_parse("Paris"), Err(WikiError::ResponseError));
+    }
+
+    #[tokio::test]
+    async fn test_get_summary_if_changed()
+    {
+        let page = Page::search("Albert Einstein").await.unwrap();
+        let revid = match page.get_summary_if_changed(None).await.unwrap() {
+            SummaryStatus::Modified { revid, .. } => revid,
+            SummaryStatus::NotModified => panic!("no previous revision was given"),
+        };
+        let status = page.get_summary_if_changed(Some(revid)).await.unwrap();
+        assert_eq!(status, SummaryStatus::NotModified);
+    }
+
+    #[tokio::test]
+    async fn test_page_protection()
+    {
+        let page = Page::search("Main Page").await.unwrap();
+        let protection = page.protection().await.unwrap();
+        assert!(protection.iter().any(|p| p.kind == "edit" && p.level == "sysop"));
+        assert!(!page.can_edit(None).await.unwrap());
+    }
+
+    #[test]
+    fn test_parse_info_response()
+    {
+        let resp: InfoResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"pages":[{"pageid":15580374,"ns":0,"title":"Main Page",
+            "contentmodel":"wikitext","protection":[{"type":"edit","level":"sysop","expiry":"infinity"},
+            {"type":"move","level":"sysop","expiry":"infinity"}],"restrictiontypes":["edit","move"],
+            "actions":{"edit":false}}]}}"#,
+        )
+        .unwrap();
+        let page = &resp.query.pages[0];
+        assert_eq!(page.protection.len(), 2);
+        assert_eq!(page.protection[0].kind, "edit");
+        assert_eq!(page.protection[0].expiry, None);
+        assert!(!page.actions.edit);
+        assert!(!page.missing);
+    }
+
+    #[test]
+    fn test_parse_opensearch()
+    {
+        let resp = serde_json::json!([
+            "Albert Ein",
+            ["Albert Einstein", "Albert Einstein Medal"],
+            ["", ""],
+            [
+                "https://en.wikipedia.org/wiki/Albert_Einstein",
+                "https://en.wikipedia.org/wiki/Albert_Einstein_Medal"
+            ]
+        ]);
+        let results = parse_opensearch(resp).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[1],
+            (
+                "Albert Einstein Medal".to_string(),
+                String::new(),
+                "https://en.wikipedia.org/wiki/Albert_Einstein_Medal".to_string()
+            )
+        );
+
+        let err = parse_opensearch(serde_json::json!(["Albert Ein", ["Albert Einstein"], [""]])).unwrap_err();
+        assert_eq!(
+            err,
+            WikiError::UnexpectedResponseShape {
+                expected: "an array of the search term and three arrays of strings".to_string(),
+                got:      r#"["Albert Ein",["Albert Einstein"],[""]]"#.to_string(),
+            }
+        );
+        assert!(!err.is_retryable());
+
+        let err = parse_opensearch(serde_json::json!({"error": {"code": "x".repeat(300)}})).unwrap_err();
+        assert!(matches!(err, WikiError::UnexpectedResponseShape { got, .. } if got.ends_with('…')));
+    }
+
+    #[test]
+    fn test_page_prefers_pageid()
+    {
+        let page = Page::new(
+            "Albert Einstein".to_string(),
+            "https://en.wikipedia.org/wiki/Albert_Einstein".to_string(),
+        );
+        assert_eq!(page.page_ref().query_param(), ("titles", "Albert Einstein".to_string()));
+
+        let page = page.with_pageid(PageId(736));
+        assert_eq!(page.page_ref().query_param(), ("pageids", "736".to_string()));
+        assert_eq!(page.page_ref().parse_param(), ("pageid", "736".to_string()));
+        assert_eq!(page.page_ref().to_string(), "page 736");
+    }
+
+    #[tokio::test]
+    async fn test_page_from_pageid()
+    {
+        let page = Page::from_pageid(PageId(736)).await.unwrap();
+        assert_eq!(&*page.title, "Albert Einstein");
+        assert_eq!(page.pageid, Some(PageId(736)));
+        assert!(page.get_summary().await.unwrap().starts_with("Albert Einstein"));
+
+        let err = Page::from_pageid(PageId(0)).await.err().unwrap();
+        assert_eq!(err, WikiError::PageNotFoundError("page 0".to_string()));
+    }
+
+    #[test]
+    fn test_merge_batched_protections()
+    {
+        let first: InfoResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"normalized":[{"fromencoded":false,"from":"main Page",
+            "to":"Main Page"}],"pages":[{"pageid":15580374,"ns":0,"title":"Main Page","protection":[
+            {"type":"edit","level":"sysop","expiry":"infinity"}]},{"ns":0,"title":"Nowhere at all",
+            "missing":true}]}}"#,
+        )
+        .unwrap();
+        let second: InfoResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"pages":[{"pageid":736,"ns":0,"title":"Albert Einstein",
+            "protection":[]}]}}"#,
+        )
+        .unwrap();
+
+        let protections = merge_protections(
+            &["Albert Einstein", "Nowhere at all", "main Page", "Albert Einstein"],
+            vec![first, second],
+        );
+        assert_eq!(protections.len(), 2);
+        assert_eq!(protections[0], ("Albert Einstein".to_string(), Vec::new()));
+        assert_eq!(protections[1].0, "main Page");
+        assert_eq!(protections[1].1[0].level, "sysop");
+    }
+
+    #[test]
+    fn test_parse_summary_formatversions()
+    {
+        let v2: SummaryResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"pages":[{"pageid":736,"ns":0,"title":"Albert Einstein",
+            "extract":"Albert Einstein was...","lastrevid":1150000000}]}}"#,
+        )
+        .unwrap();
+        let v1: SummaryResponse = serde_json::from_str(
+            r#"{"batchcomplete":"","query":{"pages":{"736":{"pageid":736,"ns":0,"title":"Albert Einstein",
+            "extract":"Albert Einstein was...","lastrevid":1150000000}}}}"#,
+        )
+        .unwrap();
+        assert_eq!(v1, v2);
+        assert!(v1.batchcomplete);
+        assert!(!v1.query.pages[0].missing);
+    }
+
+    #[test]
+    fn test_parse_missing_and_invalid_pages()
+    {
+        let v2: SummaryResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"pages":[{"ns":0,"title":"Nowhere at all","missing":true},
+            {"title":"a|b","invalidreason":"The requested page title contains invalid characters: \"|\".",
+            "invalid":true}]}}"#,
+        )
+        .unwrap();
+        let v1: SummaryResponse = serde_json::from_str(
+            r#"{"batchcomplete":"","query":{"pages":{"-1":{"ns":0,"title":"Nowhere at all","missing":""},
+            "-2":{"title":"a|b","invalidreason":"The requested page title contains invalid characters: \"|\".",
+            "invalid":""}}}}"#,
+        )
+        .unwrap();
+        assert_eq!(v1, v2);
+        assert!(v1.query.pages[0].missing);
+        assert!(!v1.query.pages[0].invalid);
+        assert!(v1.query.pages[1].invalid);
+
+        let info: InfoResponse =
+            serde_json::from_str(r#"{"query":{"pages":{"-1":{"ns":0,"title":"Nowhere at all","missing":""}}}}"#)
+                .unwrap();
+        assert!(info.query.pages[0].missing);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_parse_protection_expiry()
+    {
+        use chrono::{TimeZone, Utc};
+
+        let protection: super::Protection =
+            serde_json::from_str(r#"{"type":"edit","level":"autoconfirmed","expiry":"2024-01-31T08:00:00Z"}"#).unwrap();
+        assert_eq!(
+            protection.expiry,
+            Some(Utc.with_ymd_and_hms(2024, 1, 31, 8, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_error_is_retryable()
+    {
+        assert!(WikiError::TimeoutError.is_retryable());
+        assert!(WikiError::HttpStatusError(429).is_retryable());
+        assert!(WikiError::HttpStatusError(503).is_retryable());
+        assert!(!WikiError::HttpStatusError(404).is_retryable());
+        assert!(!WikiError::JsonParseError.is_retryable());
+        assert!(!WikiError::PageNotFoundError("x".to_string()).is_retryable());
+    }
+
+    #[test]
+    fn test_truncate_at_word_boundary()
+    {
+        assert_eq!(truncate("one two three", 20), "one two three");
+        assert_eq!(truncate("one two three", 9), "one two…");
+    }
+
+    #[test]
+    fn test_page_anchor()
+    {
+        let page = Page::new(
+            "Albert Einstein".to_string(),
+            "https://en.wikipedia.org/wiki/Albert_Einstein".to_string(),
+        );
+        assert_eq!(page.anchor(), None);
+
+        let page = Page::new(
+            "Albert Einstein#Early life".to_string(),
+            "https://en.wikipedia.org/wiki/Albert_Einstein".to_string(),
+        );
+        assert_eq!(&*page.title, "Albert Einstein");
+        assert_eq!(page.url(), "https://en.wikipedia.org/wiki/Albert_Einstein#Early_life");
+        assert_eq!(page.anchor().as_deref(), Some("Early life"));
+
+        let link = LangLink {
+            lang: "de".to_string(),
+            title: "Albert Einstein#Frühe Jahre".to_string(),
+            url: "https://de.wikipedia.org/wiki/Albert_Einstein#Fr%C3%BChe_Jahre".to_string(),
+            ..Default::default()
+        };
+        let page = link.page();
+        assert_eq!(&*page.lang, "de");
+        assert_eq!(page.url(), link.url);
+        assert_eq!(page.anchor().as_deref(), Some("Frühe Jahre"));
+    }
+
+    #[test]
+    fn test_search_outcome_ignores_case_and_spacing()
+    {
+        assert!(!SearchOutcome::new("albert  einstein", "Albert Einstein").corrected);
+        assert!(!SearchOutcome::new("Albert_Einstein", "Albert Einstein").corrected);
+        assert!(SearchOutcome::new("Albert Einstien", "Albert Einstein").corrected);
+    }
+
     #[tokio::test]
     async fn test_search_page_not_found()
     {
@@ -0,0 +1,125 @@
+//! Logging of the requests a [`WikiClient`](crate::WikiClient) makes.
+//!
+//! Requests are logged as `tracing` events at the info level, with a target
+//! per kind of endpoint so their verbosity can be set separately, e.g. with
+//! `RUST_LOG=wikipedia_api::action=info,wikipedia_api::rest=off`:
+//!
+//! - [`ACTION_TARGET`] for requests to action APIs, of the client's wiki as
+//!   well as of Commons, Wikidata and sister projects
+//! - [`REST_TARGET`] for requests to REST APIs
+//! - [`REDIRECT_TARGET`] for following redirects, e.g. of short URLs
+//!
+//! Without a `tracing` subscriber the events are emitted as `log` records.
+
+/// The target of requests to action APIs
+pub const ACTION_TARGET: &str = "wikipedia_api::action";
+
+/// The target of requests to REST APIs
+pub const REST_TARGET: &str = "wikipedia_api::rest";
+
+/// The target of requests following redirects
+pub const REDIRECT_TARGET: &str = "wikipedia_api::redirect";
+
+/// What is logged about each request, see
+/// [`WikiClientBuilder::request_logging`](crate::WikiClientBuilder::request_logging).
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RequestLogging
+{
+    /// Nothing
+    Off,
+
+    /// The URL without its query, e.g. `https://en.wikipedia.org/w/api.php`
+    UrlOnly,
+
+    /// The full URL, with the values of parameters that may be secret, like
+    /// tokens, passwords and keys, replaced by `REDACTED`
+    #[default]
+    Redacted,
+}
+
+/// The kind of endpoint a request is made to, which decides its target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Endpoint
+{
+    Action,
+    Rest,
+    Redirect,
+}
+
+impl RequestLogging
+{
+    /// Log a request to `url` at `endpoint` as the policy says
+    pub(crate) fn log(self, endpoint: Endpoint, url: &reqwest::Url)
+    {
+        let url = match self {
+            Self::Off => return,
+            Self::UrlOnly => without_query(url),
+            Self::Redacted => redacted(url),
+        };
+
+        match endpoint {
+            Endpoint::Action => tracing::info!(target: ACTION_TARGET, "Requested '{url}'"),
+            Endpoint::Rest => tracing::info!(target: REST_TARGET, "Requested '{url}'"),
+            Endpoint::Redirect => tracing::info!(target: REDIRECT_TARGET, "Requested '{url}'"),
+        }
+    }
+}
+
+/// Whether the parameter `name` may hold a secret
+fn is_secret(name: &str) -> bool
+{
+    let name = name.to_ascii_lowercase();
+    name.ends_with("token")
+        || name.ends_with("key")
+        || name.contains("password")
+        || name.contains("secret")
+        || name.starts_with("oauth")
+}
+
+fn without_query(url: &reqwest::Url) -> String
+{
+    let mut url = url.clone();
+    url.set_query(None);
+    url.set_fragment(None);
+    url.to_string()
+}
+
+fn redacted(url: &reqwest::Url) -> String
+{
+    if !url.query_pairs().any(|(name, _)| is_secret(&name)) {
+        return url.to_string();
+    }
+
+    let mut redacted = url.clone();
+    redacted
+        .query_pairs_mut()
+        .clear()
+        .extend_pairs(url.query_pairs().map(|(name, value)| {
+            let value = if is_secret(&name) { "REDACTED".into() } else { value };
+            (name, value)
+        }));
+    redacted.to_string()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{redacted, without_query};
+
+    #[test]
+    fn test_redacted()
+    {
+        let url = reqwest::Url::parse(
+            "https://en.wikipedia.org/w/api.php?action=edit&title=Sandbox&token=abc%2B%5C&lgpassword=hunter2&format=json",
+        )
+        .unwrap();
+        assert_eq!(
+            redacted(&url),
+            "https://en.wikipedia.org/w/api.php?action=edit&title=Sandbox&token=REDACTED&lgpassword=REDACTED&format=json"
+        );
+        assert_eq!(without_query(&url), "https://en.wikipedia.org/w/api.php");
+
+        let url = reqwest::Url::parse("https://en.wikipedia.org/w/api.php?action=query&titles=A%20B").unwrap();
+        assert_eq!(redacted(&url), url.as_str());
+    }
+}
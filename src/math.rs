@@ -0,0 +1,147 @@
+//! Math formulas rendered by the Math extension, for STEM study tools.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ParseResponse, Snippet, WikiClient, WikiError};
+
+/// The path of rendered SVG formulas on the Math extension's renderer
+const SVG_PATH: &str = "/media/math/render/svg/";
+
+/// The path of rendered PNG formulas on the Math extension's renderer
+const PNG_PATH: &str = "/media/math/render/png/";
+
+/// A math formula on a page.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Formula
+{
+    /// The TeX source of the formula, e.g. `{\displaystyle E=mc^{2}}`
+    pub tex: String,
+
+    /// Whether the formula is displayed as a block of its own rather than
+    /// inline with text
+    pub display: bool,
+
+    /// The URL of the formula rendered as SVG, if it was rendered
+    pub svg_url: Option<String>,
+
+    /// The URL of the formula rendered as PNG, if the page links to a PNG
+    /// rendering, as wikis configured for PNG fallback images do
+    pub png_url: Option<String>,
+}
+
+impl WikiClient
+{
+    /// Get the math formulas on the page titled `title`, in the order they
+    /// appear, with their TeX source and the URLs of their rendered images
+    pub async fn formulas(&self, title: &str) -> Result<Vec<Formula>, WikiError>
+    {
+        let resp: ParseResponse = self
+            .get_json(&[
+                ("action", "parse"),
+                ("format", "json"),
+                ("page", title),
+                ("prop", "text"),
+                ("disableeditsection", "1"),
+                ("disablelimitreport", "1"),
+                ("redirects", "1"),
+                ("formatversion", "2"),
+            ])
+            .await?;
+
+        Ok(parse_formulas(&resp.into_parse(title)?.text))
+    }
+}
+
+/// Find the `<math>` elements in rendered HTML. The TeX source is in the
+/// `alttext` attribute of each, and the fallback `<img>` following it links to
+/// the rendered SVG or PNG.
+fn parse_formulas(html: &str) -> Vec<Formula>
+{
+    let mut formulas = Vec::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find("<math") {
+        rest = &rest[start..];
+        let tag_end = rest.find('>').unwrap_or(rest.len());
+        let tag = &rest[..tag_end];
+        let element_end = rest.find("</math>").map_or(rest.len(), |x| x + "</math>".len());
+
+        let Some(tex) = attribute(tag, "alttext")
+        else {
+            rest = &rest[element_end..];
+            continue;
+        };
+
+        rest = &rest[element_end..];
+
+        // The fallback image directly follows the MathML, before the next
+        // formula.
+        let next_math = rest.find("<math").unwrap_or(rest.len());
+        let fallback = rest[..next_math].find("<img").and_then(|i| {
+            let img = &rest[i..next_math];
+            attribute(&img[..img.find('>').unwrap_or(img.len())], "src")
+        });
+
+        formulas.push(Formula {
+            tex,
+            display: attribute(tag, "display").as_deref() == Some("block"),
+            svg_url: fallback.clone().filter(|x| x.contains(SVG_PATH)),
+            png_url: fallback.filter(|x| x.contains(PNG_PATH)),
+        });
+    }
+
+    formulas
+}
+
+/// Get the decoded value of the attribute `name` of the HTML start tag `tag`
+pub(crate) fn attribute(tag: &str, name: &str) -> Option<String>
+{
+    let start = tag.find(&format!(" {name}=\""))? + name.len() + 3;
+    let end = tag[start..].find('"')? + start;
+    Some(Snippet::from_html(&tag[start..end]).text)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::parse_formulas;
+    use crate::WikiClient;
+
+    #[test]
+    fn test_parse_formulas()
+    {
+        let html = concat!(
+            r#"<p>The equation <span class="mwe-math-element"><span class="mwe-math-mathml-inline mwe-math-mathml-a11y" style="display: none;">"#,
+            r#"<math xmlns="http://www.w3.org/1998/Math/MathML" alttext="{\displaystyle E=mc^{2}}"><semantics><mrow></mrow>"#,
+            r#"<annotation encoding="application/x-tex">{\displaystyle E=mc^{2}}</annotation></semantics></math></span>"#,
+            r#"<img src="https://wikimedia.org/api/rest_v1/media/math/render/svg/8ed8" class="mwe-math-fallback-image-inline" alt="{\displaystyle E=mc^{2}}"></span>"#,
+            r#" and <math xmlns="http://www.w3.org/1998/Math/MathML" display="block" alttext="{\displaystyle a&lt;b}"></math></p>"#,
+            r#"<math alttext="x^2"></math><img src="https://wikimedia.org/api/rest_v1/media/math/render/png/ab12"></p>"#,
+        );
+
+        let formulas = parse_formulas(html);
+        assert_eq!(formulas.len(), 3);
+        assert_eq!(formulas[0].tex, r"{\displaystyle E=mc^{2}}");
+        assert!(!formulas[0].display);
+        assert_eq!(
+            formulas[0].svg_url.as_deref(),
+            Some("https://wikimedia.org/api/rest_v1/media/math/render/svg/8ed8")
+        );
+        assert!(formulas[0].png_url.is_none());
+        assert_eq!(formulas[1].tex, r"{\displaystyle a<b}");
+        assert!(formulas[1].display);
+        assert!(formulas[1].svg_url.is_none());
+        assert!(formulas[2].svg_url.is_none());
+        assert_eq!(
+            formulas[2].png_url.as_deref(),
+            Some("https://wikimedia.org/api/rest_v1/media/math/render/png/ab12")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_formulas()
+    {
+        let formulas = WikiClient::new().formulas("Mass–energy equivalence").await.unwrap();
+        assert!(formulas.iter().any(|x| x.tex.contains("mc^{2}") && x.svg_url.is_some()));
+    }
+}
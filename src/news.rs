@@ -0,0 +1,111 @@
+//! Headlines from Wikinews, e.g. for news tickers.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Timestamp, WikiClient, WikiError};
+
+/// The category Wikinews adds articles to once they are published
+const PUBLISHED_CATEGORY: &str = "Category:Published";
+
+/// A published Wikinews article.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewsArticle
+{
+    /// Title of the article, i.e. its headline
+    pub title: String,
+
+    /// When the article was published
+    pub published: Timestamp,
+
+    /// The URL of the article
+    pub url: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RCategoryMember
+{
+    pub title:     String,
+    pub timestamp: Timestamp,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct CategoryMembersQuery
+{
+    pub categorymembers: Vec<RCategoryMember>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct CategoryMembersResponse
+{
+    pub query: CategoryMembersQuery,
+}
+
+impl WikiClient
+{
+    /// Get the `latest_n` (at most 500) most recently published articles of
+    /// the Wikinews in the client's language, newest first.
+    ///
+    /// Articles are listed by when they were added to `Category:Published`,
+    /// which only exists under that name on the English Wikinews.
+    pub async fn news(&self, latest_n: usize) -> Result<Vec<NewsArticle>, WikiError>
+    {
+        let resp: CategoryMembersResponse = self
+            .get_sister_json(
+                "wikinews",
+                &[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("list", "categorymembers"),
+                    ("cmtitle", PUBLISHED_CATEGORY),
+                    ("cmnamespace", "0"),
+                    ("cmprop", "title|timestamp"),
+                    ("cmsort", "timestamp"),
+                    ("cmdir", "desc"),
+                    ("cmlimit", &latest_n.clamp(1, 500).to_string()),
+                    ("formatversion", "2"),
+                ],
+            )
+            .await?;
+
+        Ok(resp
+            .query
+            .categorymembers
+            .into_iter()
+            .map(|x| {
+                NewsArticle {
+                    url:       self.sister_page_url("wikinews", &x.title),
+                    title:     x.title,
+                    published: x.timestamp,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::CategoryMembersResponse;
+    use crate::WikiClient;
+
+    #[test]
+    fn test_parse_category_members()
+    {
+        let resp: CategoryMembersResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"continue":{"cmcontinue":"...","continue":"-||"},"query":{"categorymembers":[
+            {"pageid":1001,"ns":0,"title":"Scientists announce discovery","timestamp":"2023-05-02T08:00:00Z"},
+            {"pageid":1000,"ns":0,"title":"Election results declared","timestamp":"2023-05-01T21:30:00Z"}]}}"#,
+        )
+        .unwrap();
+        assert_eq!(resp.query.categorymembers.len(), 2);
+        assert_eq!(resp.query.categorymembers[0].title, "Scientists announce discovery");
+    }
+
+    #[tokio::test]
+    async fn test_news()
+    {
+        let news = WikiClient::new().news(5).await.unwrap();
+        assert_eq!(news.len(), 5);
+        assert!(news[0].url.starts_with("https://en.wikinews.org/wiki/"));
+    }
+}
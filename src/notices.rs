@@ -0,0 +1,259 @@
+//! Hatnotes and maintenance templates on pages, for consumers that flag or
+//! skip disambiguated, unfinished or poorly sourced articles.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{math::attribute, quotes::skip_nested, ParseResponse, Snippet, WikiClient, WikiError};
+
+/// A note at the top of a page or section pointing readers elsewhere, e.g.
+/// `This article is about the physicist. For other uses, see Einstein
+/// (disambiguation).`
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hatnote
+{
+    /// Plain text of the note
+    pub text: String,
+
+    /// Titles of the pages the note links to, in order
+    pub links: Vec<String>,
+}
+
+/// What kind of maintenance template a [`MaintenanceTag`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MaintenanceKind
+{
+    /// A cleanup banner about the whole page or a section, e.g.
+    /// `{{Unreferenced}}` or `{{Multiple issues}}`
+    Banner,
+
+    /// A notice that the page is a stub, e.g. `{{Physicist-stub}}`
+    Stub,
+
+    /// A note inline with text, e.g. `{{Citation needed}}`
+    Inline,
+}
+
+/// A maintenance template on a page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceTag
+{
+    /// The name of the template, e.g. `Unreferenced`, `Stub` or `Citation
+    /// needed`
+    pub name: String,
+
+    /// What kind of template it is
+    pub kind: MaintenanceKind,
+
+    /// Plain text of the rendered template
+    pub text: String,
+}
+
+impl WikiClient
+{
+    /// Get the hatnotes on the page titled `title`, in the order they appear,
+    /// including those at the top of sections
+    pub async fn hatnotes(&self, title: &str) -> Result<Vec<Hatnote>, WikiError>
+    {
+        Ok(parse_hatnotes(&self.page_html(title).await?))
+    }
+
+    /// Get the maintenance templates on the page titled `title`, in the order
+    /// they appear. Inline templates are listed once per occurrence.
+    pub async fn maintenance_tags(&self, title: &str) -> Result<Vec<MaintenanceTag>, WikiError>
+    {
+        Ok(parse_maintenance_tags(&self.page_html(title).await?))
+    }
+
+    /// Get the rendered HTML of the page titled `title`
+    async fn page_html(&self, title: &str) -> Result<String, WikiError>
+    {
+        let resp: ParseResponse = self
+            .get_json(&[
+                ("action", "parse"),
+                ("format", "json"),
+                ("page", title),
+                ("prop", "text"),
+                ("disableeditsection", "1"),
+                ("disablelimitreport", "1"),
+                ("redirects", "1"),
+                ("formatversion", "2"),
+            ])
+            .await?;
+
+        Ok(resp.into_parse(title)?.text)
+    }
+}
+
+/// Find the elements in `html` whose classes `matches`, returning each element
+/// along with the classes of its start tag. Elements nested in a found one are
+/// found as well.
+fn elements_with_class(html: &str, matches: impl Fn(&[&str]) -> bool) -> Vec<(&str, String)>
+{
+    let mut elements = Vec::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+        let tag_end = rest.find('>').map_or(rest.len(), |x| x + 1);
+        let tag = &rest[..tag_end];
+        let name = tag[1..]
+            .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .next()
+            .unwrap_or_default();
+
+        let classes = attribute(tag, "class").unwrap_or_default();
+        if !name.is_empty() && matches(&classes.split_whitespace().collect::<Vec<_>>()) {
+            let after = skip_nested(rest, &format!("<{name}"), &format!("</{name}>"));
+            elements.push((&rest[..rest.len() - after.len()], classes));
+        }
+
+        rest = &rest[tag_end..];
+    }
+
+    elements
+}
+
+/// Plain text of rendered HTML with whitespace collapsed
+fn plain_text(html: &str) -> String
+{
+    Snippet::from_html(html)
+        .text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Find the hatnotes in rendered HTML, the elements with the `hatnote` class
+fn parse_hatnotes(html: &str) -> Vec<Hatnote>
+{
+    elements_with_class(html, |classes| classes.contains(&"hatnote"))
+        .into_iter()
+        .map(|(element, _)| {
+            let links = element
+                .match_indices("<a ")
+                .filter_map(|(i, _)| {
+                    let link = &element[i..];
+                    attribute(&link[..link.find('>').unwrap_or(link.len())], "title")
+                })
+                .collect();
+
+            Hatnote {
+                text: plain_text(element),
+                links,
+            }
+        })
+        .collect()
+}
+
+/// Find the maintenance templates in rendered HTML. Banners are article
+/// message boxes (`ambox`) named by their `box-<Template>` class, stub notices
+/// have the `asbox` class, and inline templates the `Inline-Template` class.
+fn parse_maintenance_tags(html: &str) -> Vec<MaintenanceTag>
+{
+    let is_tag = |classes: &[&str]| {
+        classes
+            .iter()
+            .any(|x| matches!(*x, "ambox" | "asbox" | "Inline-Template"))
+    };
+
+    elements_with_class(html, is_tag)
+        .into_iter()
+        .map(|(element, classes)| {
+            let text = plain_text(element);
+            let classes: Vec<_> = classes.split_whitespace().collect();
+
+            if classes.contains(&"ambox") {
+                let name = classes
+                    .iter()
+                    .find_map(|x| x.strip_prefix("box-"))
+                    .map_or_else(|| "Unknown".to_string(), |x| x.replace('_', " "));
+                MaintenanceTag {
+                    name,
+                    kind: MaintenanceKind::Banner,
+                    text,
+                }
+            }
+            else if classes.contains(&"asbox") {
+                MaintenanceTag {
+                    name: "Stub".to_string(),
+                    kind: MaintenanceKind::Stub,
+                    text,
+                }
+            }
+            else {
+                // Inline templates show their name in brackets, e.g.
+                // `[citation needed]`
+                let mut name = text.trim_matches(|c| c == '[' || c == ']').trim().to_string();
+                if let Some(first) = name.chars().next() {
+                    name.replace_range(..first.len_utf8(), &first.to_uppercase().to_string());
+                }
+                MaintenanceTag {
+                    name,
+                    kind: MaintenanceKind::Inline,
+                    text,
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{parse_hatnotes, parse_maintenance_tags, MaintenanceKind};
+    use crate::WikiClient;
+
+    const HTML: &str = concat!(
+        r#"<div class="mw-parser-output"><div role="note" class="hatnote navigation-not-searchable">This article is about the physicist. "#,
+        r#"For other uses, see <a href="/wiki/Einstein_(disambiguation)" class="mw-disambig" title="Einstein (disambiguation)">Einstein (disambiguation)</a>.</div>"#,
+        r#"<table class="box-Multiple_issues plainlinks metadata ambox ambox-content" role="presentation"><tbody><tr><td>"#,
+        r#"<div class="mbox-text-span">This article has multiple issues.</div>"#,
+        r#"<table class="box-Unreferenced plainlinks metadata ambox ambox-content"><tbody><tr><td>This article <b>does not cite any sources</b>.</td></tr></tbody></table>"#,
+        r#"</td></tr></tbody></table>"#,
+        r#"<p>He was born in 1879.<sup class="noprint Inline-Template Template-Fact" style="white-space:nowrap;">[<i><a href="/wiki/Wikipedia:Citation_needed" "#,
+        r#"title="Wikipedia:Citation needed"><span title="This claim needs references.">citation needed</span></a></i>]</sup></p>"#,
+        r#"<h2>Career</h2><div role="note" class="hatnote">Main article: <a href="/wiki/Career_of_Einstein" title="Career of Einstein">Career of Einstein</a></div>"#,
+        r#"<div role="note" class="metadata plainlinks asbox stub"><table><tbody><tr><td><i>This article about a physicist is a <a href="/wiki/Wikipedia:Stub" title="Wikipedia:Stub">stub</a>.</i></td></tr></tbody></table></div>"#,
+        r#"</div>"#,
+    );
+
+    #[test]
+    fn test_parse_hatnotes()
+    {
+        let hatnotes = parse_hatnotes(HTML);
+        assert_eq!(hatnotes.len(), 2);
+        assert_eq!(
+            hatnotes[0].text,
+            "This article is about the physicist. For other uses, see Einstein (disambiguation)."
+        );
+        assert_eq!(hatnotes[0].links, ["Einstein (disambiguation)"]);
+        assert_eq!(hatnotes[1].text, "Main article: Career of Einstein");
+        assert_eq!(hatnotes[1].links, ["Career of Einstein"]);
+    }
+
+    #[test]
+    fn test_parse_maintenance_tags()
+    {
+        let tags = parse_maintenance_tags(HTML);
+        let names: Vec<_> = tags.iter().map(|x| (x.name.as_str(), x.kind)).collect();
+        assert_eq!(
+            names,
+            [
+                ("Multiple issues", MaintenanceKind::Banner),
+                ("Unreferenced", MaintenanceKind::Banner),
+                ("Citation needed", MaintenanceKind::Inline),
+                ("Stub", MaintenanceKind::Stub),
+            ]
+        );
+        assert_eq!(tags[1].text, "This article does not cite any sources.");
+        assert_eq!(tags[2].text, "[citation needed]");
+        assert_eq!(tags[3].text, "This article about a physicist is a stub.");
+    }
+
+    #[tokio::test]
+    async fn test_hatnotes()
+    {
+        let hatnotes = WikiClient::new().hatnotes("Mercury (planet)").await.unwrap();
+        assert!(hatnotes.iter().any(|x| x.text.contains("For other uses")));
+    }
+}
@@ -0,0 +1,160 @@
+//! Quality predictions for revisions from the [Lift Wing] scoring API, which
+//! replaced ORES, e.g. to rank cleanup candidates on editors' dashboards.
+//!
+//! Only available with the `ores` feature.
+//!
+//! [Lift Wing]: https://wikitech.wikimedia.org/wiki/Machine_Learning/LiftWing
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{WikiClient, WikiError};
+
+/// The url of the Lift Wing models
+const LIFT_WING_URL: &str = "https://api.wikimedia.org/service/lw/inference/v1/models";
+
+/// A model to score revisions with.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ScoreModel
+{
+    /// The assessment class the article is predicted to have as of the
+    /// revision, e.g. `Stub`, `C` or `FA`
+    ArticleQuality,
+
+    /// Whether a new article is predicted to be `OK`, `spam`, `vandalism` or an
+    /// `attack`
+    DraftQuality,
+}
+
+impl ScoreModel
+{
+    /// The name of the model as used by the API
+    pub fn as_str(&self) -> &'static str
+    {
+        match self {
+            Self::ArticleQuality => "articlequality",
+            Self::DraftQuality => "draftquality",
+        }
+    }
+}
+
+/// The prediction of a model for a revision.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Score
+{
+    /// The most likely class, e.g. `Stub`
+    pub prediction: String,
+
+    /// The probability of each class, between 0 and 1
+    pub probability: BTreeMap<String, f64>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RScoreError
+{
+    #[serde(default, rename = "type")]
+    pub kind:    String,
+    #[serde(default)]
+    pub message: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RModelScore
+{
+    pub score: Option<Score>,
+    pub error: Option<RScoreError>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RWikiScores
+{
+    /// Scores by revision id, then by model
+    #[serde(default)]
+    pub scores: HashMap<String, HashMap<String, RModelScore>>,
+}
+
+/// Scores by wiki, e.g. `enwiki`
+pub(crate) type ScoreResponse = HashMap<String, RWikiScores>;
+
+#[derive(Serialize)]
+struct ScoreRequest
+{
+    rev_id: i64,
+}
+
+impl WikiClient
+{
+    /// Score the revision `revid` of this client's Wikipedia with `model`.
+    ///
+    /// Fails with [`WikiError::PageNotFoundError`] if the revision doesn't
+    /// exist or was deleted. Not every model is available for every language.
+    pub async fn score(&self, revid: i64, model: ScoreModel) -> Result<Score, WikiError>
+    {
+        let wiki = format!("{}wiki", self.language().replace('-', "_"));
+        let url = format!("{LIFT_WING_URL}/{wiki}-{}:predict", model.as_str());
+
+        let resp: ScoreResponse = self.post_json(&url, &ScoreRequest { rev_id: revid }).await?;
+        find_score(resp, &wiki, revid, model)
+    }
+}
+
+/// Pick the score of `revid` by `model` out of a response
+fn find_score(mut resp: ScoreResponse, wiki: &str, revid: i64, model: ScoreModel) -> Result<Score, WikiError>
+{
+    let score = resp
+        .remove(wiki)
+        .and_then(|mut x| x.scores.remove(&revid.to_string()))
+        .and_then(|mut x| x.remove(model.as_str()));
+
+    match score {
+        Some(RModelScore { score: Some(x), .. }) => Ok(x),
+        Some(RModelScore { error: Some(e), .. }) if e.kind.contains("NotFound") || e.kind.contains("Deleted") => {
+            Err(WikiError::PageNotFoundError(format!("revision {revid}")))
+        }
+        _ => Err(WikiError::ResponseError),
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{find_score, ScoreModel, ScoreResponse};
+    use crate::{WikiClient, WikiError};
+
+    #[test]
+    fn test_parse_score()
+    {
+        let resp: ScoreResponse = serde_json::from_str(
+            r#"{"enwiki":{"models":{"articlequality":{"version":"0.9.2"}},"scores":{"1100000000":{
+            "articlequality":{"score":{"prediction":"GA","probability":{"B":0.2,"C":0.05,"FA":0.1,"GA":0.6,
+            "Start":0.04,"Stub":0.01}}}}}}}"#,
+        )
+        .unwrap();
+        let score = find_score(resp, "enwiki", 1100000000, ScoreModel::ArticleQuality).unwrap();
+        assert_eq!(score.prediction, "GA");
+        assert_eq!(score.probability["FA"], 0.1);
+    }
+
+    #[test]
+    fn test_parse_score_error()
+    {
+        let resp: ScoreResponse = serde_json::from_str(
+            r#"{"enwiki":{"models":{"draftquality":{"version":"0.2.1"}},"scores":{"1":{"draftquality":{
+            "error":{"message":"RevisionNotFound: Could not find revision ({revision}:1)","type":"RevisionNotFound"}}}}}}"#,
+        )
+        .unwrap();
+        let err = find_score(resp, "enwiki", 1, ScoreModel::DraftQuality).err().unwrap();
+        assert!(matches!(err, WikiError::PageNotFoundError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_score()
+    {
+        let score = WikiClient::new()
+            .score(1100000000, ScoreModel::ArticleQuality)
+            .await
+            .unwrap();
+        assert!(!score.prediction.is_empty());
+    }
+}
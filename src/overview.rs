@@ -0,0 +1,355 @@
+//! Batched overviews of pages combining their summary, coordinates and lead
+//! image, e.g. for populating map pins.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::Continue,
+    types::{deserialize_flag, deserialize_pages, resolve_title, TitleMapping},
+    AuthSession, Coordinates, PageId, Thumbnail, WikiClient, WikiError,
+};
+
+/// The summary, coordinates and thumbnail of a page.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageOverview
+{
+    /// Title of the page
+    pub title: String,
+
+    /// Id of the page
+    pub pageid: PageId,
+
+    /// The canonical URL of the page
+    pub url: String,
+
+    /// Plain text of the lead section
+    pub extract: String,
+
+    /// The primary coordinates of the page, if it has any
+    pub coordinates: Option<Coordinates>,
+
+    /// A thumbnail of the lead image, if the page has one
+    pub thumbnail: Option<Thumbnail>,
+}
+
+/// Everything needed to unfurl a link to a page, e.g. in a chat bot.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PagePreview
+{
+    /// Title of the page
+    pub title: String,
+
+    /// The short description of the page, e.g. "German-born theoretical
+    /// physicist (1879–1955)"
+    pub description: Option<String>,
+
+    /// Plain text of the lead section
+    pub extract: String,
+
+    /// The URL of a thumbnail of the lead image, if the page has one
+    pub thumbnail_url: Option<String>,
+
+    /// The canonical URL of the page
+    pub canonical_url: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RPreviewPage
+{
+    pub title:        String,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub missing:      bool,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub invalid:      bool,
+    pub description:  Option<String>,
+    #[serde(default)]
+    pub extract:      String,
+    pub thumbnail:    Option<Thumbnail>,
+    #[serde(default)]
+    pub canonicalurl: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PreviewQuery
+{
+    #[serde(default, deserialize_with = "deserialize_pages")]
+    pub pages: Vec<RPreviewPage>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PreviewResponse
+{
+    pub query: PreviewQuery,
+}
+
+impl From<RPreviewPage> for PagePreview
+{
+    fn from(page: RPreviewPage) -> Self
+    {
+        Self {
+            title:         page.title,
+            description:   page.description,
+            extract:       page.extract,
+            thumbnail_url: page.thumbnail.map(|x| x.source),
+            canonical_url: page.canonicalurl,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ROverviewPage
+{
+    pub title:        String,
+    #[serde(default)]
+    pub pageid:       PageId,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub missing:      bool,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub invalid:      bool,
+    pub extract:      Option<String>,
+    #[serde(default)]
+    pub coordinates:  Vec<Coordinates>,
+    pub thumbnail:    Option<Thumbnail>,
+    pub canonicalurl: Option<String>,
+    pub index:        Option<u32>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct OverviewQuery
+{
+    #[serde(default)]
+    pub normalized: Vec<TitleMapping>,
+    #[serde(default)]
+    pub redirects:  Vec<TitleMapping>,
+    #[serde(default, deserialize_with = "deserialize_pages")]
+    pub pages:      Vec<ROverviewPage>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct OverviewResponse
+{
+    #[serde(rename = "continue")]
+    pub(crate) cont:   Option<Continue>,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub batchcomplete: bool,
+    #[serde(default)]
+    pub query:         OverviewQuery,
+}
+
+impl WikiClient
+{
+    /// Get a preview of the page titled `title` for link unfurling, in one
+    /// request
+    pub async fn preview(&self, title: &str) -> Result<PagePreview, WikiError>
+    {
+        let resp: PreviewResponse = self
+            .get_json(&[
+                ("action", "query"),
+                ("format", "json"),
+                ("prop", "description|extracts|pageimages|info"),
+                ("titles", title),
+                ("exintro", "1"),
+                ("explaintext", "1"),
+                ("exchars", "500"),
+                ("piprop", "thumbnail"),
+                ("pithumbsize", "640"),
+                ("inprop", "url"),
+                ("redirects", "1"),
+                ("formatversion", "2"),
+            ])
+            .await?;
+
+        match resp.query.pages.into_iter().next() {
+            Some(x) if x.missing || x.invalid => Err(WikiError::PageNotFoundError(title.to_string())),
+            Some(x) => Ok(x.into()),
+            None => Err(WikiError::ResponseError),
+        }
+    }
+
+    /// Get the overviews of the pages titled `titles`, in batched requests of
+    /// 50 titles each. The overviews are returned in the order of `titles`;
+    /// titles that don't exist are left out.
+    pub async fn overview_many(&self, titles: &[&str]) -> Result<Vec<PageOverview>, WikiError>
+    {
+        self.overview_many_as(titles, None).await
+    }
+
+    /// Like [`Self::overview_many`], but make the requests as the user of
+    /// `session`, so they hold 500 titles each if it's a [bot
+    /// session](AuthSession::bot)
+    pub async fn overview_many_as(
+        &self,
+        titles: &[&str],
+        session: Option<&AuthSession>,
+    ) -> Result<Vec<PageOverview>, WikiError>
+    {
+        let responses: Vec<OverviewResponse> = self
+            .get_json_batched(
+                &[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("prop", "extracts|coordinates|pageimages|info"),
+                    ("exintro", "1"),
+                    ("explaintext", "1"),
+                    ("exlimit", "max"),
+                    ("colimit", "max"),
+                    ("piprop", "thumbnail"),
+                    ("pithumbsize", "320"),
+                    ("pilimit", "max"),
+                    ("inprop", "url"),
+                    ("redirects", "1"),
+                    ("formatversion", "2"),
+                ],
+                titles,
+                session,
+                |x: &OverviewResponse| x.cont.clone(),
+            )
+            .await?;
+
+        Ok(merge_overviews(titles, responses))
+    }
+}
+
+/// Merge the partial pages of continued responses and order them like `titles`
+fn merge_overviews(titles: &[&str], responses: Vec<OverviewResponse>) -> Vec<PageOverview>
+{
+    let mut mappings = Vec::new();
+    for resp in &responses {
+        mappings.extend(resp.query.normalized.iter().cloned());
+        mappings.extend(resp.query.redirects.iter().cloned());
+    }
+    let overviews = fold_overviews(responses);
+
+    let mut ordered = Vec::with_capacity(overviews.len());
+    for title in titles {
+        let title = resolve_title(&mappings, title);
+        if ordered.iter().any(|x: &PageOverview| x.title == title) {
+            continue;
+        }
+        if let Some((_, overview)) = overviews.iter().find(|(_, x)| x.title == title) {
+            ordered.push(overview.clone());
+        }
+    }
+
+    ordered
+}
+
+/// Merge the partial pages of continued responses into overviews, in the
+/// order they first appear, along with the index a generator gave each page
+pub(crate) fn fold_overviews(responses: Vec<OverviewResponse>) -> Vec<(Option<u32>, PageOverview)>
+{
+    let mut overviews: Vec<(Option<u32>, PageOverview)> = Vec::new();
+
+    for resp in responses {
+        for page in resp.query.pages.into_iter().filter(|x| !x.missing && !x.invalid) {
+            let i = match overviews.iter().position(|(_, x)| x.title == page.title) {
+                Some(i) => i,
+                None => {
+                    let overview = PageOverview {
+                        title: page.title.clone(),
+                        pageid: page.pageid,
+                        ..Default::default()
+                    };
+                    overviews.push((page.index, overview));
+                    overviews.len() - 1
+                }
+            };
+
+            let (index, overview) = &mut overviews[i];
+            if page.index.is_some() {
+                *index = page.index;
+            }
+            if let Some(extract) = page.extract {
+                overview.extract = extract;
+            }
+            if let Some(coordinates) = page.coordinates.first() {
+                overview.coordinates = Some(*coordinates);
+            }
+            if page.thumbnail.is_some() {
+                overview.thumbnail = page.thumbnail;
+            }
+            if let Some(url) = page.canonicalurl {
+                overview.url = url;
+            }
+        }
+    }
+
+    overviews
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{merge_overviews, OverviewResponse, PagePreview, PreviewResponse};
+    use crate::WikiClient;
+
+    #[test]
+    fn test_merge_continued_overviews()
+    {
+        let first: OverviewResponse = serde_json::from_str(
+            r#"{"continue":{"excontinue":1,"continue":"||coordinates|pageimages"},"query":{
+            "normalized":[{"fromencoded":false,"from":"eiffel Tower","to":"Eiffel Tower"}],
+            "redirects":[{"from":"Big Ben","to":"Big Ben (bell)"}],
+            "pages":[{"pageid":9232,"ns":0,"title":"Eiffel Tower","extract":"The Eiffel Tower is...",
+            "coordinates":[{"lat":48.8583,"lon":2.2944,"primary":true,"globe":"earth"}],
+            "thumbnail":{"source":"https://upload.wikimedia.org/eiffel.jpg","width":213,"height":320}},
+            {"pageid":3592,"ns":0,"title":"Big Ben (bell)","coordinates":[{"lat":51.5007,"lon":-0.1245,
+            "primary":true,"globe":"earth"}],"canonicalurl":"https://en.wikipedia.org/wiki/Big_Ben_(bell)"},
+            {"ns":0,"title":"Nowhere at all","missing":true}]}}"#,
+        )
+        .unwrap();
+        let second: OverviewResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"pages":[{"pageid":9232,"ns":0,"title":"Eiffel Tower"},
+            {"pageid":3592,"ns":0,"title":"Big Ben (bell)","extract":"Big Ben is..."}]}}"#,
+        )
+        .unwrap();
+        assert!(first.cont.is_some());
+        assert!(second.cont.is_none());
+
+        let overviews = merge_overviews(&["Big Ben", "Nowhere at all", "eiffel Tower"], vec![first, second]);
+        assert_eq!(overviews.len(), 2);
+        assert_eq!(overviews[0].title, "Big Ben (bell)");
+        assert_eq!(overviews[0].extract, "Big Ben is...");
+        assert_eq!(overviews[0].url, "https://en.wikipedia.org/wiki/Big_Ben_(bell)");
+        assert!(overviews[0].thumbnail.is_none());
+        assert_eq!(overviews[1].title, "Eiffel Tower");
+        assert_eq!(overviews[1].extract, "The Eiffel Tower is...");
+        assert_eq!(overviews[1].coordinates.unwrap().lat, 48.8583);
+    }
+
+    #[test]
+    fn test_parse_preview()
+    {
+        let resp: PreviewResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"pages":[{"pageid":736,"ns":0,"title":"Albert Einstein",
+            "description":"German-born theoretical physicist (1879–1955)","descriptionsource":"local",
+            "extract":"Albert Einstein was a German-born theoretical physicist...","thumbnail":{
+            "source":"https://upload.wikimedia.org/einstein.jpg","width":512,"height":640},
+            "pageimage":"Albert_Einstein_Head.jpg","contentmodel":"wikitext","pagelanguage":"en",
+            "fullurl":"https://en.wikipedia.org/wiki/Albert_Einstein",
+            "canonicalurl":"https://en.wikipedia.org/wiki/Albert_Einstein"}]}}"#,
+        )
+        .unwrap();
+        let preview: PagePreview = resp.query.pages.into_iter().next().unwrap().into();
+        assert_eq!(
+            preview.description.as_deref(),
+            Some("German-born theoretical physicist (1879–1955)")
+        );
+        assert_eq!(
+            preview.thumbnail_url.as_deref(),
+            Some("https://upload.wikimedia.org/einstein.jpg")
+        );
+        assert_eq!(preview.canonical_url, "https://en.wikipedia.org/wiki/Albert_Einstein");
+    }
+
+    #[tokio::test]
+    async fn test_overview_many()
+    {
+        let overviews = WikiClient::new()
+            .overview_many(&["Eiffel Tower", "Big Ben"])
+            .await
+            .unwrap();
+        assert_eq!(overviews.len(), 2);
+        assert!(overviews.iter().all(|x| x.coordinates.is_some()));
+    }
+}
@@ -0,0 +1,211 @@
+//! Quotes from Wikiquote, e.g. for quote-of-the-day bots.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ParseResponse, WikiClient, WikiError};
+
+/// Sections of Wikiquote pages whose lists aren't quotes
+const SKIPPED_SECTIONS: &[&str] = &["External links", "See also", "References", "Sources", "Notes"];
+
+/// A quote from a Wikiquote page.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quote
+{
+    /// Plain text of the quote
+    pub text: String,
+
+    /// The heading of the section the quote is listed under, e.g. `Quotes`,
+    /// `1910s` or `Misattributed`
+    pub section: String,
+
+    /// Where the quote is from, if the page says, e.g. a letter or a book
+    pub attribution: Option<String>,
+}
+
+impl WikiClient
+{
+    /// Get the quotes on the Wikiquote page titled `title`, in the client's
+    /// language, in the order they are listed
+    pub async fn quotes(&self, title: &str) -> Result<Vec<Quote>, WikiError>
+    {
+        let resp: ParseResponse = self
+            .get_sister_json(
+                "wikiquote",
+                &[
+                    ("action", "parse"),
+                    ("format", "json"),
+                    ("page", title),
+                    ("prop", "wikitext"),
+                    ("redirects", "1"),
+                    ("formatversion", "2"),
+                ],
+            )
+            .await?;
+
+        Ok(parse_quotes(&resp.into_parse(title)?.wikitext))
+    }
+}
+
+/// Parse the quotes out of the wikitext of a Wikiquote page. Quotes are the
+/// top-level items (`*`) of lists, and the first second-level item (`**`)
+/// below a quote is its attribution.
+fn parse_quotes(wikitext: &str) -> Vec<Quote>
+{
+    let mut quotes: Vec<Quote> = Vec::new();
+    let mut section = String::new();
+
+    for line in wikitext.lines().map(str::trim_end) {
+        if line.starts_with('=') && line.ends_with('=') {
+            section = line.trim_matches('=').trim().to_string();
+            continue;
+        }
+        if SKIPPED_SECTIONS.contains(&section.as_str()) {
+            continue;
+        }
+
+        if let Some(item) = line.strip_prefix("**") {
+            if item.starts_with('*') {
+                continue;
+            }
+            if let Some(quote) = quotes.last_mut().filter(|x| x.attribution.is_none()) {
+                let attribution = strip_wikitext(item);
+                if !attribution.is_empty() {
+                    quote.attribution = Some(attribution);
+                }
+            }
+        }
+        else if let Some(item) = line.strip_prefix('*') {
+            let text = strip_wikitext(item);
+            if !text.is_empty() {
+                quotes.push(Quote {
+                    text,
+                    section: section.clone(),
+                    attribution: None,
+                });
+            }
+        }
+    }
+
+    quotes
+}
+
+/// Reduce a line of wikitext to its plain text: links are replaced by their
+/// labels, and templates, references, HTML tags and bold/italic markup are
+/// removed.
+fn strip_wikitext(wikitext: &str) -> String
+{
+    let mut text = String::new();
+    let mut rest = wikitext;
+
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with("{{") {
+            rest = skip_nested(rest, "{{", "}}");
+        }
+        else if rest.starts_with("<ref") {
+            rest = match rest.find('>') {
+                Some(end) if rest[..end].ends_with('/') => &rest[end + 1..],
+                _ => rest.find("</ref>").map_or("", |end| &rest[end + "</ref>".len()..]),
+            };
+        }
+        else if c == '<' {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+        }
+        else if rest.starts_with("[[") {
+            let end = rest.find("]]").unwrap_or(rest.len());
+            let link = &rest[2..end];
+            // Links to files and categories don't show up as text
+            if !link.starts_with("File:") && !link.starts_with("Image:") && !link.starts_with("Category:") {
+                text.push_str(link.rsplit('|').next().unwrap_or(link));
+            }
+            rest = rest.get(end + 2..).unwrap_or("");
+        }
+        else if c == '[' {
+            let end = rest.find(']').unwrap_or(rest.len());
+            if let Some((_, label)) = rest[1..end].split_once(' ') {
+                text.push_str(label);
+            }
+            rest = rest.get(end + 1..).unwrap_or("");
+        }
+        else if rest.starts_with("''") {
+            rest = rest.trim_start_matches('\'');
+        }
+        else {
+            text.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Skip past the (possibly nested) block opened at the start of `s`
+pub(crate) fn skip_nested<'a>(s: &'a str, open: &str, close: &str) -> &'a str
+{
+    let mut depth = 0;
+    let mut rest = s;
+
+    while !rest.is_empty() {
+        if rest.starts_with(open) {
+            depth += 1;
+            rest = &rest[open.len()..];
+        }
+        else if rest.starts_with(close) {
+            depth -= 1;
+            rest = &rest[close.len()..];
+            if depth == 0 {
+                return rest;
+            }
+        }
+        else {
+            let c = rest.chars().next().unwrap_or_default();
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    rest
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{parse_quotes, strip_wikitext};
+    use crate::WikiClient;
+
+    #[test]
+    fn test_strip_wikitext()
+    {
+        assert_eq!(
+            strip_wikitext(" '''Strike''' [[Albert Einstein|Einstein]]'s [[physics]]{{citation needed|date={{now}}}}"),
+            "Strike Einstein's physics"
+        );
+        assert_eq!(
+            strip_wikitext("A quote.<ref name=\"a\">Book, p. 3</ref><ref name=\"a\" /> [https://example.org Source]"),
+            "A quote. Source"
+        );
+    }
+
+    #[test]
+    fn test_parse_quotes()
+    {
+        let wikitext = "[[File:Einstein.jpg|thumb]]\n'''Albert Einstein''' was a physicist.\n\n== Quotes ==\n=== \
+                        1910s ===\n* ''Imagination is more important than knowledge.''\n** [[The Saturday Evening \
+                        Post]] (1929)\n*** Reprinted in ''Cosmic Religion''\n* Everything should be made as simple as \
+                        possible.\n== See also ==\n* [[Relativity]]\n";
+        let quotes = parse_quotes(wikitext);
+        assert_eq!(quotes.len(), 2);
+        assert_eq!(quotes[0].text, "Imagination is more important than knowledge.");
+        assert_eq!(quotes[0].section, "1910s");
+        assert_eq!(
+            quotes[0].attribution.as_deref(),
+            Some("The Saturday Evening Post (1929)")
+        );
+        assert_eq!(quotes[1].attribution, None);
+    }
+
+    #[tokio::test]
+    async fn test_quotes()
+    {
+        let quotes = WikiClient::new().quotes("Albert Einstein").await.unwrap();
+        assert!(quotes.iter().any(|x| x.attribution.is_some()));
+    }
+}
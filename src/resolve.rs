@@ -0,0 +1,245 @@
+//! Resolving a search term to a page through a chain of increasingly lenient
+//! lookups.
+
+use std::sync::Arc;
+
+use crate::{FullTextSearchOptions, Page, PageRef, SearchOptions, WikiClient, WikiError};
+
+/// A lookup tried by [`WikiClient::resolve`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ResolveStep
+{
+    /// The search term is the title of a page or a redirect
+    ExactTitle,
+
+    /// The best title match of an `opensearch` query, which corrects some
+    /// misspellings
+    OpenSearch,
+
+    /// The best match of a full-text search
+    FullTextSearch,
+}
+
+/// A lookup tried by [`WikiClient::resolve`] on the Wikipedia of one language.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ResolveAttempt
+{
+    /// Language code of the Wikipedia the lookup was made on
+    pub lang: String,
+
+    /// The lookup
+    pub step: ResolveStep,
+}
+
+/// The page a search term was resolved to, and how.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Resolution
+{
+    /// The page that was found
+    pub page: Page,
+
+    /// The lookups that were tried, in order. The last one found the page.
+    pub path: Vec<ResolveAttempt>,
+}
+
+impl Resolution
+{
+    /// The lookup that found the page, `None` if `path` is empty, which it
+    /// never is for resolutions returned by [`WikiClient::resolve`]
+    pub fn found_by(&self) -> Option<&ResolveAttempt> { self.path.last() }
+}
+
+/// The lookups [`WikiClient::resolve`] tries, in order, until one finds a page.
+///
+/// Each enabled step is tried on the client's Wikipedia first, then all of
+/// them again on the Wikipedia of each fallback language.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FallbackPolicy
+{
+    exact_title: bool,
+    opensearch:  bool,
+    full_text:   bool,
+    languages:   Vec<String>,
+}
+
+impl Default for FallbackPolicy
+{
+    fn default() -> Self
+    {
+        Self {
+            exact_title: true,
+            opensearch:  true,
+            full_text:   false,
+            languages:   Vec::new(),
+        }
+    }
+}
+
+impl FallbackPolicy
+{
+    /// Create a `FallbackPolicy` with the defaults: the exact title, then
+    /// `opensearch`, on the client's Wikipedia only.
+    pub fn new() -> Self { Self::default() }
+
+    /// Whether to look the search term up as an exact title
+    pub fn exact_title(mut self, enabled: bool) -> Self
+    {
+        self.exact_title = enabled;
+        self
+    }
+
+    /// Whether to fall back to the best `opensearch` match
+    pub fn opensearch(mut self, enabled: bool) -> Self
+    {
+        self.opensearch = enabled;
+        self
+    }
+
+    /// Whether to fall back to the best full-text search match
+    pub fn full_text(mut self, enabled: bool) -> Self
+    {
+        self.full_text = enabled;
+        self
+    }
+
+    /// The languages whose Wikipedias to fall back to, in order, e.g.
+    /// `&["de", "fr"]`
+    pub fn languages(mut self, languages: &[&str]) -> Self
+    {
+        self.languages = languages.iter().map(|x| x.to_string()).collect();
+        self
+    }
+
+    /// The lookups to try, in order, on the Wikipedia of `own_language` and
+    /// the fallback languages
+    fn attempts(&self, own_language: &str) -> Vec<ResolveAttempt>
+    {
+        let steps: Vec<ResolveStep> = [
+            (self.exact_title, ResolveStep::ExactTitle),
+            (self.opensearch, ResolveStep::OpenSearch),
+            (self.full_text, ResolveStep::FullTextSearch),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, step)| enabled.then_some(step))
+        .collect();
+
+        let mut languages = vec![own_language];
+        for lang in &self.languages {
+            if !languages.contains(&lang.as_str()) {
+                languages.push(lang);
+            }
+        }
+
+        languages
+            .into_iter()
+            .flat_map(|lang| {
+                steps.iter().map(move |step| {
+                    ResolveAttempt {
+                        lang: lang.to_string(),
+                        step: *step,
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+impl WikiClient
+{
+    /// Resolve `search_term` to a page by trying the lookups of `policy` in
+    /// order until one finds a page. [`Resolution::path`] reports which
+    /// lookups were tried.
+    ///
+    /// Fails with [`WikiError::PageNotFoundError`] if none of them finds a
+    /// page, or with the first error other than that.
+    pub async fn resolve(&self, search_term: &str, policy: &FallbackPolicy) -> Result<Resolution, WikiError>
+    {
+        let search_term = search_term.trim();
+        let mut path = Vec::new();
+
+        for attempt in policy.attempts(self.language()) {
+            let client = self.clone().with_language(&attempt.lang);
+            let found = match attempt.step {
+                ResolveStep::ExactTitle => client.info(PageRef::Title(search_term)).await.map(|x| x.title),
+                ResolveStep::OpenSearch => {
+                    client
+                        .search(search_term, &SearchOptions::default())
+                        .await
+                        .map(|mut x| x.swap_remove(0).title.to_string())
+                }
+                ResolveStep::FullTextSearch => {
+                    client
+                        .full_text_search(search_term, &FullTextSearchOptions::new().limit(1))
+                        .await
+                        .and_then(|x| {
+                            x.into_iter()
+                                .next()
+                                .map(|x| x.title)
+                                .ok_or_else(|| WikiError::PageNotFoundError(search_term.to_string()))
+                        })
+                }
+            };
+
+            path.push(attempt);
+
+            match found {
+                Ok(title) => {
+                    let page = Page::new(title, String::new()).with_client(&client);
+                    let page = Page {
+                        url: Arc::from(page.canonical_url()),
+                        ..page
+                    };
+                    return Ok(Resolution { page, path });
+                }
+                Err(WikiError::PageNotFoundError(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(WikiError::PageNotFoundError(search_term.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{FallbackPolicy, ResolveAttempt, ResolveStep};
+    use crate::WikiClient;
+
+    #[test]
+    fn test_fallback_attempts()
+    {
+        let attempts = FallbackPolicy::new()
+            .exact_title(false)
+            .full_text(true)
+            .languages(&["de", "en"])
+            .attempts("en");
+        let attempt = |lang: &str, step| {
+            ResolveAttempt {
+                lang: lang.to_string(),
+                step,
+            }
+        };
+        assert_eq!(
+            attempts,
+            [
+                attempt("en", ResolveStep::OpenSearch),
+                attempt("en", ResolveStep::FullTextSearch),
+                attempt("de", ResolveStep::OpenSearch),
+                attempt("de", ResolveStep::FullTextSearch),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_falls_back()
+    {
+        let resolution = WikiClient::new()
+            .resolve("Albert Einsten", &FallbackPolicy::new())
+            .await
+            .unwrap();
+        assert_eq!(&*resolution.page.title, "Albert Einstein");
+        assert_eq!(resolution.path.len(), 2);
+        assert_eq!(resolution.found_by().unwrap().step, ResolveStep::OpenSearch);
+    }
+}
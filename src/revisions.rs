@@ -0,0 +1,268 @@
+//! Historical revisions of pages, e.g. to show a page as it was on a date.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    title,
+    types::{deserialize_flag, deserialize_pages, format_timestamp},
+    PageId, PageRef, ParseResponse, Revision, Timestamp, WikiClient, WikiError,
+};
+
+/// The content of a page as of one revision.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevisionContent
+{
+    /// Title of the page the revision belongs to
+    pub title: String,
+
+    /// The revision
+    pub revision: Revision,
+
+    /// The wikitext of the page as of the revision
+    pub wikitext: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RSlot
+{
+    #[serde(default)]
+    pub content:    String,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub texthidden: bool,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RRevision
+{
+    #[serde(flatten)]
+    pub revision:   Revision,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub texthidden: bool,
+    #[serde(default)]
+    pub slots:      HashMap<String, RSlot>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RRevisionsPage
+{
+    #[serde(default)]
+    pub title:     String,
+    pub pageid:    Option<PageId>,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub missing:   bool,
+    #[serde(default)]
+    pub revisions: Vec<RRevision>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RevisionsQuery
+{
+    #[serde(default, deserialize_with = "deserialize_pages")]
+    pub pages: Vec<RRevisionsPage>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RevisionsResponse
+{
+    #[serde(default)]
+    pub query: RevisionsQuery,
+}
+
+impl WikiClient
+{
+    /// Get the wikitext of the revision `revid`, along with the revision's
+    /// details.
+    ///
+    /// Fails with [`WikiError::PageNotFoundError`] if there is no such
+    /// revision, or it was deleted or its text was hidden.
+    pub async fn revision_content(&self, revid: i64) -> Result<RevisionContent, WikiError>
+    {
+        to_revision_content(revid, self.fetch_revision_content(revid).await?)
+    }
+
+    /// Like [`Self::revision_content`], but fail with
+    /// [`WikiError::PageNotFoundError`] unless the revision is of `page`
+    pub(crate) async fn revision_content_of(&self, page: PageRef<'_>, revid: i64)
+        -> Result<RevisionContent, WikiError>
+    {
+        let resp = self.fetch_revision_content(revid).await?;
+        if !is_revision_of(page, &resp) {
+            return Err(WikiError::PageNotFoundError(format!("revision {revid} of {page}")));
+        }
+        to_revision_content(revid, resp)
+    }
+
+    async fn fetch_revision_content(&self, revid: i64) -> Result<RevisionsResponse, WikiError>
+    {
+        self.get_json(&[
+            ("action", "query"),
+            ("format", "json"),
+            ("prop", "revisions"),
+            ("revids", &revid.to_string()),
+            ("rvprop", "ids|user|timestamp|comment|size|flags|content"),
+            ("rvslots", "main"),
+            ("formatversion", "2"),
+        ])
+        .await
+    }
+
+    /// Get the rendered HTML of the revision `revid`
+    pub async fn revision_html(&self, revid: i64) -> Result<String, WikiError>
+    {
+        let resp: ParseResponse = self
+            .get_json(&[
+                ("action", "parse"),
+                ("format", "json"),
+                ("oldid", &revid.to_string()),
+                ("prop", "text"),
+                ("disableeditsection", "1"),
+                ("disablelimitreport", "1"),
+                ("formatversion", "2"),
+            ])
+            .await?;
+
+        Ok(resp.into_parse(&format!("revision {revid}"))?.text)
+    }
+
+    /// Get the revision of the page titled `title` that was current at
+    /// `timestamp`, i.e. the last one made at or before it.
+    ///
+    /// Fails with [`WikiError::PageNotFoundError`] if the page doesn't exist
+    /// or didn't exist yet at `timestamp`.
+    pub async fn revision_at(&self, title: &str, timestamp: &Timestamp) -> Result<Revision, WikiError>
+    {
+        self.revision_of_at(PageRef::Title(title), timestamp).await
+    }
+
+    pub(crate) async fn revision_of_at(&self, page: PageRef<'_>, timestamp: &Timestamp) -> Result<Revision, WikiError>
+    {
+        let (key, value) = page.query_param();
+        let resp: RevisionsResponse = self
+            .get_json(&[
+                ("action", "query"),
+                ("format", "json"),
+                ("prop", "revisions"),
+                (key, &value),
+                ("rvprop", "ids|user|timestamp|comment|size|flags"),
+                ("rvlimit", "1"),
+                ("rvstart", &format_timestamp(timestamp)),
+                ("rvdir", "older"),
+                ("redirects", "1"),
+                ("formatversion", "2"),
+            ])
+            .await?;
+
+        resp.query
+            .pages
+            .into_iter()
+            .find(|x| !x.missing)
+            .and_then(|x| x.revisions.into_iter().next())
+            .map(|x| x.revision)
+            .ok_or_else(|| WikiError::PageNotFoundError(page.to_string()))
+    }
+}
+
+/// Get the content of revision `revid` out of the response of
+/// [`WikiClient::revision_content`]
+/// Whether the revision in `resp` is of `page`, comparing ids if `page` is
+/// referred to by id, and normalized titles otherwise
+fn is_revision_of(page: PageRef<'_>, resp: &RevisionsResponse) -> bool
+{
+    resp.query.pages.first().is_some_and(|x| {
+        match page {
+            PageRef::Id(id) => x.pageid == Some(id),
+            PageRef::Title(title) => title::normalize(&x.title) == title::normalize(title),
+        }
+    })
+}
+
+fn to_revision_content(revid: i64, resp: RevisionsResponse) -> Result<RevisionContent, WikiError>
+{
+    let not_found = || WikiError::PageNotFoundError(format!("revision {revid}"));
+    let page = resp.query.pages.into_iter().next().ok_or_else(not_found)?;
+    let mut revision = page.revisions.into_iter().next().ok_or_else(not_found)?;
+
+    let main = revision.slots.remove("main").unwrap_or_default();
+    if revision.texthidden || main.texthidden {
+        return Err(not_found());
+    }
+
+    Ok(RevisionContent {
+        title:    page.title,
+        wikitext: main.content,
+        revision: revision.revision,
+    })
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{is_revision_of, to_revision_content, RevisionsResponse};
+    use crate::{PageId, PageRef, WikiError};
+
+    #[test]
+    fn test_parse_revision_content()
+    {
+        let resp: RevisionsResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"pages":[{"pageid":736,"ns":0,"title":"Albert Einstein",
+            "revisions":[{"revid":1000000,"parentid":999999,"minor":true,"user":"Someone",
+            "timestamp":"2022-01-01T00:00:00Z","size":150000,"comment":"typo","slots":{"main":{
+            "contentmodel":"wikitext","contentformat":"text/x-wiki","content":"'''Albert Einstein''' was..."}}}]}]}}"#,
+        )
+        .unwrap();
+        let revision = &resp.query.pages[0].revisions[0];
+        assert_eq!(revision.revision.revid, 1000000);
+        assert!(revision.revision.minor);
+        assert_eq!(revision.slots["main"].content, "'''Albert Einstein''' was...");
+
+        assert!(is_revision_of(PageRef::Title("albert_Einstein"), &resp));
+        assert!(is_revision_of(PageRef::Id(PageId(736)), &resp));
+        assert!(!is_revision_of(PageRef::Title("Isaac Newton"), &resp));
+        assert!(!is_revision_of(PageRef::Id(PageId(14627)), &resp));
+    }
+
+    #[test]
+    fn test_hidden_revision_text()
+    {
+        let resp: RevisionsResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"pages":[{"pageid":736,"ns":0,"title":"Albert Einstein",
+            "revisions":[{"revid":1000000,"parentid":999999,"user":"Someone","timestamp":"2022-01-01T00:00:00Z",
+            "size":150000,"comment":"vandalism","texthidden":true,"slots":{"main":{"contentmodel":"wikitext",
+            "contentformat":"text/x-wiki","texthidden":true}}}]}]}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            to_revision_content(1000000, resp),
+            Err(WikiError::PageNotFoundError("revision 1000000".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_bad_revision()
+    {
+        let resp: RevisionsResponse =
+            serde_json::from_str(r#"{"batchcomplete":true,"query":{"badrevids":{"1":{"revid":1,"missing":true}}}}"#)
+                .unwrap();
+        assert!(resp.query.pages.is_empty());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[tokio::test]
+    async fn test_revision_at()
+    {
+        use chrono::{TimeZone, Utc};
+
+        use crate::WikiClient;
+
+        let client = WikiClient::new();
+        let timestamp = Utc.with_ymd_and_hms(2010, 1, 1, 0, 0, 0).unwrap();
+        let revision = client.revision_at("Albert Einstein", &timestamp).await.unwrap();
+        assert!(revision.timestamp <= timestamp);
+
+        let content = client.revision_content(revision.revid).await.unwrap();
+        assert_eq!(content.title, "Albert Einstein");
+        assert!(content.wikitext.contains("Einstein"));
+    }
+}
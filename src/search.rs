@@ -0,0 +1,640 @@
+//! Full-text search through CirrusSearch, with snippets that highlight the
+//! matched terms.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::{extra_params_setter, with_continue, with_extra_params, Continue},
+    overview::{fold_overviews, OverviewResponse},
+    types::{deserialize_flag, deserialize_pages},
+    PageId, PageOverview, Paged, Revision, SearchHit, Snippet, Timestamp, WikiClient, WikiError,
+};
+
+/// The CirrusSearch relevance profile (`srqiprofile`) used to rank results.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub enum RelevanceProfile
+{
+    /// Let the search engine pick the best profile
+    #[default]
+    EngineAutoselect,
+
+    /// Rank on text relevance and incoming links
+    Classic,
+
+    /// Rank on text relevance only
+    ClassicNoBoostLinks,
+
+    /// Weighted sum of text relevance and incoming links
+    WsumIncLinks,
+
+    /// Weighted sum of text relevance, incoming links and page views
+    WsumIncLinksPv,
+
+    /// Rank mostly on incoming links
+    PopularIncLinks,
+
+    /// Rank mostly on incoming links and page views
+    PopularIncLinksPv,
+
+    /// Any other profile supported by the wiki
+    Custom(String),
+}
+
+impl RelevanceProfile
+{
+    /// The value of the profile as used by the API
+    pub fn as_str(&self) -> &str
+    {
+        match self {
+            Self::EngineAutoselect => "engine_autoselect",
+            Self::Classic => "classic",
+            Self::ClassicNoBoostLinks => "classic_noboostlinks",
+            Self::WsumIncLinks => "wsum_inclinks",
+            Self::WsumIncLinksPv => "wsum_inclinks_pv",
+            Self::PopularIncLinks => "popular_inclinks",
+            Self::PopularIncLinksPv => "popular_inclinks_pv",
+            Self::Custom(x) => x,
+        }
+    }
+}
+
+/// How snippets of search results are returned.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SnippetFormat
+{
+    /// The HTML returned by the API, with matched terms wrapped in
+    /// `<span class="searchmatch">`
+    Html,
+
+    /// Plain text, with the byte ranges of matched terms in
+    /// [`Snippet::highlights`]
+    #[default]
+    Plain,
+}
+
+/// Options for [`WikiClient::full_text_search`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FullTextSearchOptions
+{
+    limit:          usize,
+    namespace:      i64,
+    profile:        RelevanceProfile,
+    snippet_format: SnippetFormat,
+    rerank:         bool,
+    extra_params:   Vec<(String, String)>,
+}
+
+impl Default for FullTextSearchOptions
+{
+    fn default() -> Self
+    {
+        Self {
+            limit:          10,
+            namespace:      0,
+            profile:        RelevanceProfile::default(),
+            snippet_format: SnippetFormat::default(),
+            rerank:         false,
+            extra_params:   Vec::new(),
+        }
+    }
+}
+
+impl FullTextSearchOptions
+{
+    extra_params_setter!();
+
+    /// Create `FullTextSearchOptions` with the defaults: 10 articles ranked by
+    /// the engine's choice of profile, with plain text snippets.
+    pub fn new() -> Self { Self::default() }
+
+    /// The maximum number of results to return, between 1 and 500.
+    pub fn limit(mut self, limit: usize) -> Self
+    {
+        self.limit = limit.clamp(1, 500);
+        self
+    }
+
+    /// The namespace to search in, `0` being articles.
+    pub fn namespace(mut self, namespace: i64) -> Self
+    {
+        self.namespace = namespace;
+        self
+    }
+
+    /// The relevance profile to rank results with
+    pub fn profile(mut self, profile: RelevanceProfile) -> Self
+    {
+        self.profile = profile;
+        self
+    }
+
+    /// How to return snippets
+    pub fn snippet_format(mut self, snippet_format: SnippetFormat) -> Self
+    {
+        self.snippet_format = snippet_format;
+        self
+    }
+
+    /// Re-rank the results locally by how closely their titles match the
+    /// search term, and set [`SearchHit::score`]. See [`rerank`].
+    pub fn rerank(mut self, rerank: bool) -> Self
+    {
+        self.rerank = rerank;
+        self
+    }
+}
+
+/// Score each hit by the [Jaro-Winkler similarity] of the query to its title
+/// or best-matching alias, ignoring case and extra whitespace, and sort the
+/// hits best score first. Hits with equal scores keep their order.
+///
+/// The score is set as [`SearchHit::score`], so callers can apply a confidence
+/// threshold before picking the top hit.
+///
+/// [Jaro-Winkler similarity]: https://en.wikipedia.org/wiki/Jaro%E2%80%93Winkler_distance
+pub fn rerank(query: &str, mut hits: Vec<SearchHit>) -> Vec<SearchHit>
+{
+    let query = fold(query);
+    for hit in &mut hits {
+        let score = std::iter::once(&hit.title)
+            .chain(&hit.aliases)
+            .map(|x| jaro_winkler(&query, &fold(x)))
+            .fold(0.0, f64::max);
+        hit.score = Some(score);
+    }
+
+    hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    hits
+}
+
+/// Lowercase `s` and collapse its whitespace, for comparing titles
+fn fold(s: &str) -> Vec<char>
+{
+    s.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .chars()
+        .collect()
+}
+
+/// The Jaro-Winkler similarity of `a` and `b`, from 0 (nothing in common) to
+/// 1 (equal)
+fn jaro_winkler(a: &[char], b: &[char]) -> f64
+{
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    // Characters match if they are equal and not further apart than this.
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut a_matched = vec![false; a.len()];
+    let mut b_matched = vec![false; b.len()];
+    let mut matches = 0;
+
+    for (i, x) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+        for j in start..end {
+            if !b_matched[j] && b[j] == *x {
+                a_matched[i] = true;
+                b_matched[j] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+
+    if matches == 0 {
+        return 0.0;
+    }
+
+    let a_seq = a.iter().zip(&a_matched).filter(|(_, m)| **m).map(|(x, _)| x);
+    let b_seq = b.iter().zip(&b_matched).filter(|(_, m)| **m).map(|(x, _)| x);
+    let transpositions = a_seq.zip(b_seq).filter(|(x, y)| x != y).count() / 2;
+
+    let m = matches as f64;
+    let jaro = (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0;
+
+    let prefix = a.iter().zip(b).take(4).take_while(|(x, y)| x == y).count();
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RSearchHit
+{
+    pub title:         String,
+    pub pageid:        PageId,
+    #[serde(default)]
+    pub size:          u64,
+    #[serde(default)]
+    pub wordcount:     u64,
+    #[serde(default)]
+    pub snippet:       String,
+    pub timestamp:     Timestamp,
+    pub redirecttitle: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct FullTextSearchQuery
+{
+    pub search: Vec<RSearchHit>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct FullTextSearchResponse
+{
+    #[serde(rename = "continue")]
+    pub(crate) cont: Option<Continue>,
+    pub query:       FullTextSearchQuery,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RRedirect
+{
+    pub from:  String,
+    pub to:    String,
+    pub index: Option<u32>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RSuggestPage
+{
+    pub title:     String,
+    #[serde(default)]
+    pub pageid:    PageId,
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub missing:   bool,
+    pub index:     Option<u32>,
+    #[serde(default)]
+    pub length:    u64,
+    #[serde(default)]
+    pub revisions: Vec<Revision>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SuggestQuery
+{
+    #[serde(default)]
+    pub redirects: Vec<RRedirect>,
+    #[serde(default, deserialize_with = "deserialize_pages")]
+    pub pages:     Vec<RSuggestPage>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SuggestResponse
+{
+    #[serde(default)]
+    pub query: SuggestQuery,
+}
+
+impl WikiClient
+{
+    /// Get up to `limit` pages whose titles start with `prefix`, for
+    /// autocompletion. Redirects are folded into the page they point to, whose
+    /// [`SearchHit::aliases`] lists the redirect titles that matched, so every
+    /// page appears only once.
+    pub async fn suggest(&self, prefix: &str, limit: usize) -> Result<Vec<SearchHit>, WikiError>
+    {
+        let resp: SuggestResponse = self
+            .get_json(&[
+                ("action", "query"),
+                ("format", "json"),
+                ("generator", "prefixsearch"),
+                ("gpssearch", prefix),
+                ("gpsnamespace", "0"),
+                ("gpslimit", &limit.clamp(1, 500).to_string()),
+                ("prop", "info|revisions"),
+                ("rvprop", "ids|timestamp"),
+                ("redirects", "1"),
+                ("formatversion", "2"),
+            ])
+            .await?;
+
+        Ok(fold_redirects(resp.query))
+    }
+
+    /// Search the full text of pages, best match first
+    pub async fn full_text_search(
+        &self,
+        search_term: &str,
+        options: &FullTextSearchOptions,
+    ) -> Result<Vec<SearchHit>, WikiError>
+    {
+        Ok(self.full_text_search_paged(search_term, options).await?.items)
+    }
+
+    /// Like [`Self::full_text_search`], but with the continuation to get the
+    /// next hits with [`Self::resume_full_text_search`]
+    pub async fn full_text_search_paged(
+        &self,
+        search_term: &str,
+        options: &FullTextSearchOptions,
+    ) -> Result<Paged<SearchHit>, WikiError>
+    {
+        self.fetch_full_text_search(search_term, options, None).await
+    }
+
+    /// Get the next batch of [`Self::full_text_search_paged`], continuing from
+    /// `cont`
+    pub async fn resume_full_text_search(
+        &self,
+        search_term: &str,
+        options: &FullTextSearchOptions,
+        cont: &Continue,
+    ) -> Result<Paged<SearchHit>, WikiError>
+    {
+        self.fetch_full_text_search(search_term, options, Some(cont)).await
+    }
+
+    /// Search the full text of pages and get the [`PageOverview`] of each
+    /// hit, best match first, with `search` as the generator of a query for
+    /// the extracts, thumbnails and URLs of the hits. Up to 20 hits, the most
+    /// extracts the API returns at once, this takes only one request instead
+    /// of one for the search and one per hit. The overviews have no
+    /// coordinates, and the `snippet_format` and `rerank` of `options` don't
+    /// apply.
+    pub async fn search_overviews(
+        &self,
+        search_term: &str,
+        options: &FullTextSearchOptions,
+    ) -> Result<Vec<PageOverview>, WikiError>
+    {
+        let namespace = options.namespace.to_string();
+        let limit = options.limit.to_string();
+        let params = with_extra_params(
+            &[
+                ("action", "query"),
+                ("format", "json"),
+                ("generator", "search"),
+                ("gsrsearch", search_term),
+                ("gsrnamespace", &namespace),
+                ("gsrlimit", &limit),
+                ("gsrqiprofile", options.profile.as_str()),
+                ("prop", "extracts|pageimages|info"),
+                ("exintro", "1"),
+                ("explaintext", "1"),
+                ("exlimit", "max"),
+                ("piprop", "thumbnail"),
+                ("pithumbsize", "320"),
+                ("pilimit", "max"),
+                ("inprop", "url"),
+                ("formatversion", "2"),
+            ],
+            &options.extra_params,
+        );
+        // Continue only until the extracts of every hit are in, not on to the
+        // next page of hits
+        let responses: Vec<OverviewResponse> = self
+            .get_json_continued(&params, |x: &OverviewResponse| {
+                x.cont.clone().filter(|_| !x.batchcomplete)
+            })
+            .await?;
+
+        Ok(rank_overviews(responses))
+    }
+
+    async fn fetch_full_text_search(
+        &self,
+        search_term: &str,
+        options: &FullTextSearchOptions,
+        cont: Option<&Continue>,
+    ) -> Result<Paged<SearchHit>, WikiError>
+    {
+        let cont = cont.map(Continue::params).unwrap_or_default();
+        let namespace = options.namespace.to_string();
+        let limit = options.limit.to_string();
+        let params = with_extra_params(
+            &[
+                ("action", "query"),
+                ("format", "json"),
+                ("list", "search"),
+                ("srsearch", search_term),
+                ("srnamespace", &namespace),
+                ("srlimit", &limit),
+                ("srqiprofile", options.profile.as_str()),
+                ("srprop", "size|wordcount|timestamp|snippet|redirecttitle"),
+                ("formatversion", "2"),
+            ],
+            &options.extra_params,
+        );
+        let resp: FullTextSearchResponse = self.get_json(&with_continue(&params, &cont)).await?;
+
+        let hits = resp
+            .query
+            .search
+            .into_iter()
+            .map(|x| {
+                let snippet = match options.snippet_format {
+                    SnippetFormat::Html => {
+                        Snippet {
+                            text:       x.snippet,
+                            highlights: Vec::new(),
+                        }
+                    }
+                    SnippetFormat::Plain => Snippet::from_html(&x.snippet),
+                };
+
+                SearchHit {
+                    title: x.title,
+                    pageid: x.pageid,
+                    size: x.size,
+                    wordcount: x.wordcount,
+                    snippet,
+                    timestamp: Some(x.timestamp),
+                    aliases: x.redirecttitle.into_iter().collect(),
+                    score: None,
+                }
+            })
+            .collect();
+
+        Ok(Paged {
+            items: if options.rerank {
+                rerank(search_term, hits)
+            }
+            else {
+                hits
+            },
+            cont:  resp.cont,
+        })
+    }
+}
+
+/// Order the overviews of a search generator query by their rank in the search
+fn rank_overviews(responses: Vec<OverviewResponse>) -> Vec<PageOverview>
+{
+    let mut ranked = fold_overviews(responses);
+    ranked.sort_by_key(|(index, _)| index.unwrap_or(u32::MAX));
+    ranked.into_iter().map(|(_, overview)| overview).collect()
+}
+
+/// Turn the pages of a redirect-resolving generator query into search hits,
+/// with the titles of matched redirects as aliases of their targets, ordered
+/// by the best rank of the page or any of its redirects.
+fn fold_redirects(query: SuggestQuery) -> Vec<SearchHit>
+{
+    let mut ranked: Vec<(u32, SearchHit)> = query
+        .pages
+        .into_iter()
+        .filter(|x| !x.missing)
+        .map(|page| {
+            let redirects: Vec<&RRedirect> = query.redirects.iter().filter(|r| r.to == page.title).collect();
+            let rank = redirects
+                .iter()
+                .filter_map(|r| r.index)
+                .chain(page.index)
+                .min()
+                .unwrap_or(u32::MAX);
+
+            let hit = SearchHit {
+                aliases: redirects.iter().map(|r| r.from.clone()).collect(),
+                title: page.title,
+                pageid: page.pageid,
+                size: page.length,
+                timestamp: page.revisions.into_iter().next().map(|x| x.timestamp),
+                ..Default::default()
+            };
+            (rank, hit)
+        })
+        .collect();
+
+    ranked.sort_by_key(|(rank, _)| *rank);
+    ranked.into_iter().map(|(_, hit)| hit).collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{
+        fold, fold_redirects, jaro_winkler, rank_overviews, rerank, FullTextSearchOptions, RelevanceProfile,
+        SuggestResponse,
+    };
+    use crate::{overview::OverviewResponse, SearchHit, WikiClient};
+
+    #[test]
+    fn test_jaro_winkler()
+    {
+        let sim = |a: &str, b: &str| jaro_winkler(&fold(a), &fold(b));
+        assert_eq!(sim("Paris", "paris"), 1.0);
+        assert_eq!(sim("abc", "xyz"), 0.0);
+        assert!((sim("MARTHA", "MARHTA") - 0.961).abs() < 0.001);
+        assert!((sim("DIXON", "DICKSONX") - 0.813).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_rerank()
+    {
+        let hit = |title: &str, aliases: &[&str]| {
+            SearchHit {
+                title: title.to_string(),
+                aliases: aliases.iter().map(|x| x.to_string()).collect(),
+                ..Default::default()
+            }
+        };
+        let hits = rerank(
+            "einstein",
+            vec![
+                hit("Einstein family", &[]),
+                hit("Albert Einstein", &["Einstein"]),
+                hit("Theory of relativity", &[]),
+            ],
+        );
+        assert_eq!(hits[0].title, "Albert Einstein");
+        assert_eq!(hits[0].score, Some(1.0));
+        assert_eq!(hits[1].title, "Einstein family");
+        assert!(hits[1].score.unwrap() > hits[2].score.unwrap());
+    }
+
+    #[test]
+    fn test_fold_redirects()
+    {
+        let resp: SuggestResponse = serde_json::from_str(
+            r#"{"batchcomplete":true,"query":{"redirects":[{"index":1,"from":"Einstein","to":"Albert Einstein"},
+            {"index":3,"from":"Einstein (surname)","to":"Einstein (disambiguation)"},
+            {"index":4,"from":"Albert einstein","to":"Albert Einstein"}],
+            "pages":[{"pageid":736,"ns":0,"title":"Albert Einstein","length":180000,"touched":"2023-05-02T08:00:00Z",
+            "revisions":[{"revid":1150000000,"parentid":1149999999,"timestamp":"2023-05-01T12:00:00Z"}]},
+            {"pageid":1004,"ns":0,"title":"Einstein (disambiguation)","index":2,"length":3000},
+            {"pageid":2000,"ns":0,"title":"Einsteinium","length":20000}]}}"#,
+        )
+        .unwrap();
+        let hits = fold_redirects(resp.query);
+        assert_eq!(hits.len(), 3);
+        assert_eq!(hits[0].title, "Albert Einstein");
+        assert_eq!(hits[0].aliases, ["Einstein", "Albert einstein"]);
+        assert_eq!(
+            hits[0].timestamp,
+            Some(crate::types::parse_timestamp("2023-05-01T12:00:00Z").unwrap())
+        );
+        assert_eq!(hits[2].timestamp, None);
+        assert_eq!(hits[1].title, "Einstein (disambiguation)");
+        assert_eq!(hits[1].aliases, ["Einstein (surname)"]);
+        assert_eq!(hits[2].title, "Einsteinium");
+    }
+
+    #[test]
+    fn test_rank_overviews()
+    {
+        let first: OverviewResponse = serde_json::from_str(
+            r#"{"continue":{"excontinue":1,"continue":"||pageimages|info"},"query":{"pages":[
+            {"pageid":2000,"ns":0,"title":"Special relativity","index":2,"extract":"In physics, special...",
+            "canonicalurl":"https://en.wikipedia.org/wiki/Special_relativity"},
+            {"pageid":30001,"ns":0,"title":"Theory of relativity","index":1,
+            "thumbnail":{"source":"https://upload.wikimedia.org/relativity.jpg","width":320,"height":240}}]}}"#,
+        )
+        .unwrap();
+        let second: OverviewResponse = serde_json::from_str(
+            r#"{"continue":{"gsroffset":2,"continue":"gsroffset||"},"batchcomplete":true,"query":{"pages":[
+            {"pageid":30001,"ns":0,"title":"Theory of relativity","index":1,"extract":"The theory of relativity..."},
+            {"pageid":2000,"ns":0,"title":"Special relativity","index":2}]}}"#,
+        )
+        .unwrap();
+        assert!(!first.batchcomplete);
+        assert!(second.batchcomplete);
+
+        let overviews = rank_overviews(vec![first, second]);
+        assert_eq!(overviews.len(), 2);
+        assert_eq!(overviews[0].title, "Theory of relativity");
+        assert_eq!(overviews[0].extract, "The theory of relativity...");
+        assert!(overviews[0].thumbnail.is_some());
+        assert_eq!(overviews[1].title, "Special relativity");
+        assert_eq!(overviews[1].url, "https://en.wikipedia.org/wiki/Special_relativity");
+    }
+
+    #[tokio::test]
+    async fn test_search_overviews()
+    {
+        let overviews = WikiClient::new()
+            .search_overviews("theory of relativity", &FullTextSearchOptions::new().limit(3))
+            .await
+            .unwrap();
+        assert_eq!(overviews.len(), 3);
+        assert!(overviews.iter().all(|x| !x.extract.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_suggest()
+    {
+        let hits = WikiClient::new().suggest("Einstein", 10).await.unwrap();
+        let titles: Vec<&str> = hits.iter().map(|h| h.title.as_str()).collect();
+        assert!(titles.contains(&"Albert Einstein"));
+        assert!(titles.windows(2).all(|w| w[0] != w[1]));
+    }
+
+    #[tokio::test]
+    async fn test_full_text_search()
+    {
+        let hits = WikiClient::new()
+            .full_text_search(
+                "theory of relativity",
+                &FullTextSearchOptions::new().limit(3).profile(RelevanceProfile::Classic),
+            )
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 3);
+        assert!(hits.iter().all(|h| !h.snippet.highlights.is_empty()));
+    }
+}
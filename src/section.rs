@@ -0,0 +1,178 @@
+//! Plain text of single sections of a page, e.g. just the "Early life" of a
+//! biography.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{quotes::skip_nested, truncate, ParseResponse, Snippet, WikiClient, WikiError};
+
+/// Elements of rendered sections that aren't part of their prose
+const SKIPPED_ELEMENTS: &[&str] = &["style", "script", "table", "figure", "h2", "h3", "h4", "h5", "h6"];
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RSection
+{
+    pub line:       String,
+    #[serde(default)]
+    pub anchor:     String,
+    pub index:      String,
+    pub byteoffset: Option<u64>,
+}
+
+impl WikiClient
+{
+    /// Get the plain text of the section titled `name` (ignoring case) of the
+    /// page titled `title`, including its subsections, cut to at most `chars`
+    /// characters at a word boundary.
+    ///
+    /// Fails with [`WikiError::PageNotFoundError`] if the page doesn't exist
+    /// or has no such section.
+    pub async fn summarize_section(&self, title: &str, name: &str, chars: usize) -> Result<String, WikiError>
+    {
+        let resp: ParseResponse = self
+            .get_json(&[
+                ("action", "parse"),
+                ("format", "json"),
+                ("page", title),
+                ("prop", "sections"),
+                ("redirects", "1"),
+                ("formatversion", "2"),
+            ])
+            .await?;
+
+        let sections = resp.into_parse(title)?.sections;
+        let Some(index) = find_section(&sections, name)
+        else {
+            return Err(WikiError::PageNotFoundError(format!("{title}#{name}")));
+        };
+
+        let resp: ParseResponse = self
+            .get_json(&[
+                ("action", "parse"),
+                ("format", "json"),
+                ("page", title),
+                ("prop", "text"),
+                ("section", index),
+                ("disableeditsection", "1"),
+                ("disablelimitreport", "1"),
+                ("redirects", "1"),
+                ("formatversion", "2"),
+            ])
+            .await?;
+
+        Ok(truncate(&section_text(&resp.into_parse(title)?.text), chars))
+    }
+}
+
+/// Find the index of the section titled `name` for the `section` parameter.
+///
+/// Sections transcluded from templates have no byte offset in the page and
+/// an index like `T-1` that refers to the template, so only sections with a
+/// byte offset are considered.
+fn find_section<'a>(sections: &'a [RSection], name: &str) -> Option<&'a str>
+{
+    let name = name.trim().replace('_', " ").to_lowercase();
+    sections
+        .iter()
+        .filter(|x| x.byteoffset.is_some())
+        .find(|x| {
+            Snippet::from_html(&x.line).text.to_lowercase() == name || x.anchor.replace('_', " ").to_lowercase() == name
+        })
+        .map(|x| x.index.as_str())
+}
+
+/// Reduce the rendered HTML of a section to its prose, with one paragraph per
+/// line, leaving out headings, tables, figures and reference markers
+fn section_text(html: &str) -> String
+{
+    let mut kept = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        kept.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let name_end = rest[1..]
+            .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .map_or(rest.len(), |x| x + 1);
+        let name = &rest[1..name_end];
+
+        if SKIPPED_ELEMENTS.contains(&name) {
+            rest = skip_nested(rest, &format!("<{name}"), &format!("</{name}>"));
+        }
+        else if name == "sup" && rest[..rest.find('>').unwrap_or(rest.len())].contains("reference") {
+            rest = rest.find("</sup>").map_or("", |x| &rest[x + "</sup>".len()..]);
+        }
+        else {
+            let end = rest.find('>').map_or(rest.len(), |x| x + 1);
+            kept.push_str(&rest[..end]);
+            rest = &rest[end..];
+        }
+    }
+    kept.push_str(rest);
+
+    Snippet::from_html(&kept)
+        .text
+        .lines()
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{find_section, section_text};
+    use crate::{ParseResponse, WikiClient};
+
+    #[test]
+    fn test_find_section()
+    {
+        let resp: ParseResponse = serde_json::from_str(
+            r#"{"parse":{"title":"Albert Einstein","pageid":736,"sections":[
+            {"toclevel":1,"level":"2","line":"Life and career","number":"1","index":"1","fromtitle":"Albert_Einstein",
+            "byteoffset":9000,"anchor":"Life_and_career","linkAnchor":"Life_and_career"},
+            {"toclevel":2,"level":"3","line":"Early life","number":"1.1","index":"2","fromtitle":"Albert_Einstein",
+            "byteoffset":9500,"anchor":"Early_life","linkAnchor":"Early_life"},
+            {"toclevel":1,"level":"2","line":"<i>Annus mirabilis</i> papers","number":"2","index":"3",
+            "fromtitle":"Albert_Einstein","byteoffset":20000,"anchor":"Annus_mirabilis_papers"},
+            {"toclevel":1,"level":"2","line":"Notes","number":"3","index":"T-1","fromtitle":"Template:Notes",
+            "byteoffset":null,"anchor":"Notes"}]}}"#,
+        )
+        .unwrap();
+        let sections = resp.parse.unwrap().sections;
+
+        assert_eq!(find_section(&sections, "early life"), Some("2"));
+        assert_eq!(find_section(&sections, "Annus mirabilis papers"), Some("3"));
+        assert_eq!(find_section(&sections, "Early_life"), Some("2"));
+        assert_eq!(find_section(&sections, "Notes"), None);
+    }
+
+    #[test]
+    fn test_section_text()
+    {
+        let html = concat!(
+            r#"<div class="mw-parser-output"><div class="mw-heading mw-heading3"><h3 id="Early_life">Early life</h3></div>"#,
+            r#"<figure typeof="mw:File/Thumb"><a href="/wiki/File:Einstein_1882.jpg"><img src="x.jpg"></a>"#,
+            "<figcaption>Einstein in 1882</figcaption></figure>\n",
+            r##"<p>Albert Einstein was born in <a href="/wiki/Ulm">Ulm</a>.<sup id="cite_ref-1" class="reference"><a href="#cite_note-1">[1]</a></sup>"##,
+            "</p>\n<table class=\"infobox\"><tr><td><table><tr><td>inner</td></tr></table></td></tr></table>\n",
+            "<p>He attended a Catholic school &amp; later a gymnasium.</p></div>",
+        );
+        assert_eq!(
+            section_text(html),
+            "Albert Einstein was born in Ulm.\nHe attended a Catholic school & later a gymnasium."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_summarize_section()
+    {
+        let text = WikiClient::new()
+            .summarize_section("Albert Einstein", "Early life", 200)
+            .await
+            .unwrap();
+        assert!(text.contains("Ulm"));
+        assert!(text.chars().count() <= 201);
+    }
+}
@@ -0,0 +1,176 @@
+//! [`tower::Service`](tower_service::Service) integration, so standard
+//! middleware like rate limiting, retries, load shedding and tracing can be
+//! composed around Wikipedia calls.
+//!
+//! Only available with the `tower` feature.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tower_service::Service;
+
+use crate::{
+    FullTextSearchOptions, LogEvent, LogEventsOptions, NearbyPage, NewPage, NewPagesOptions, Page, PageOverview,
+    SearchHit, SearchOptions, SummaryOptions, WikiClient, WikiError,
+};
+
+/// A call to one of the [`WikiClient`] endpoints.
+#[derive(Clone, Debug)]
+pub enum WikiRequest
+{
+    /// [`WikiClient::search`]
+    Search
+    {
+        search_term: String,
+        options:     SearchOptions,
+    },
+
+    /// [`WikiClient::summary_with_options`]
+    Summary
+    {
+        title: String, options: SummaryOptions
+    },
+
+    /// [`WikiClient::full_text_search`]
+    FullTextSearch
+    {
+        search_term: String,
+        options:     FullTextSearchOptions,
+    },
+
+    /// [`WikiClient::suggest`]
+    Suggest
+    {
+        prefix: String, limit: usize
+    },
+
+    /// [`WikiClient::overview_many`]
+    OverviewMany
+    {
+        titles: Vec<String>
+    },
+
+    /// [`WikiClient::nearby`]
+    Nearby
+    {
+        lat:    f64,
+        lon:    f64,
+        radius: u32,
+        limit:  usize,
+    },
+
+    /// [`WikiClient::new_pages`]
+    NewPages(NewPagesOptions),
+
+    /// [`WikiClient::log_events`]
+    LogEvents(LogEventsOptions),
+}
+
+/// The result of a [`WikiRequest`], with one variant per request variant.
+#[derive(Clone, Debug)]
+pub enum WikiResponse
+{
+    /// [`WikiClient::search`]
+    Search(Vec<Page>),
+
+    /// [`WikiClient::summary_with_options`]
+    Summary(String),
+
+    /// [`WikiClient::full_text_search`]
+    FullTextSearch(Vec<SearchHit>),
+
+    /// [`WikiClient::suggest`]
+    Suggest(Vec<SearchHit>),
+
+    /// [`WikiClient::overview_many`]
+    OverviewMany(Vec<PageOverview>),
+
+    /// [`WikiClient::nearby`]
+    Nearby(Vec<NearbyPage>),
+
+    /// [`WikiClient::new_pages`]
+    NewPages(Vec<NewPage>),
+
+    /// [`WikiClient::log_events`]
+    LogEvents(Vec<LogEvent>),
+}
+
+impl WikiClient
+{
+    /// Make the call described by `request`
+    pub async fn call(&self, request: WikiRequest) -> Result<WikiResponse, WikiError>
+    {
+        Ok(match request {
+            WikiRequest::Search { search_term, options } => {
+                WikiResponse::Search(self.search(&search_term, &options).await?)
+            }
+            WikiRequest::Summary { title, options } => {
+                WikiResponse::Summary(self.summary_with_options(&title, &options).await?)
+            }
+            WikiRequest::FullTextSearch { search_term, options } => {
+                WikiResponse::FullTextSearch(self.full_text_search(&search_term, &options).await?)
+            }
+            WikiRequest::Suggest { prefix, limit } => WikiResponse::Suggest(self.suggest(&prefix, limit).await?),
+            WikiRequest::OverviewMany { titles } => {
+                let titles: Vec<&str> = titles.iter().map(String::as_str).collect();
+                WikiResponse::OverviewMany(self.overview_many(&titles).await?)
+            }
+            WikiRequest::Nearby {
+                lat,
+                lon,
+                radius,
+                limit,
+            } => WikiResponse::Nearby(self.nearby(lat, lon, radius, limit).await?),
+            WikiRequest::NewPages(options) => WikiResponse::NewPages(self.new_pages(&options).await?),
+            WikiRequest::LogEvents(options) => WikiResponse::LogEvents(self.log_events(&options).await?),
+        })
+    }
+}
+
+impl Service<WikiRequest> for WikiClient
+{
+    type Error = WikiError;
+    type Future = Pin<Box<dyn Future<Output = Result<WikiResponse, WikiError>> + Send>>;
+    type Response = WikiResponse;
+
+    /// A `WikiClient` is always ready; apply back pressure with a middleware
+    /// like `tower::limit::RateLimit`.
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), WikiError>> { Poll::Ready(Ok(())) }
+
+    fn call(&mut self, request: WikiRequest) -> Self::Future
+    {
+        let client = self.clone();
+        Box::pin(async move { WikiClient::call(&client, request).await })
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::future::poll_fn;
+
+    use tower_service::Service;
+
+    use super::{WikiRequest, WikiResponse};
+    use crate::{SummaryOptions, WikiClient};
+
+    #[tokio::test]
+    async fn test_service_summary()
+    {
+        let mut client = WikiClient::new();
+        poll_fn(|cx| client.poll_ready(cx)).await.unwrap();
+        let resp = Service::call(
+            &mut client,
+            WikiRequest::Summary {
+                title:   "Albert Einstein".to_string(),
+                options: SummaryOptions::new(),
+            },
+        )
+        .await
+        .unwrap();
+        assert!(matches!(resp, WikiResponse::Summary(x) if !x.is_empty()));
+    }
+}
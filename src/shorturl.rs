@@ -0,0 +1,95 @@
+//! Short `w.wiki` links, e.g. for chat bots posting compact links.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ApiError, Page, WikiClient, WikiError};
+
+/// The action API of Meta-Wiki, which hosts the URL shortener of all Wikimedia
+/// wikis
+const META_API_URL: &str = "https://meta.wikimedia.org/w/api.php";
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RShortUrl
+{
+    pub shorturl: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ShortenUrlResponse
+{
+    pub shortenurl: Option<RShortUrl>,
+    pub error:      Option<ApiError>,
+}
+
+impl WikiClient
+{
+    /// Get a short `https://w.wiki/...` link to the canonical URL of `page`.
+    ///
+    /// Shortening the same URL again returns the same link.
+    pub async fn shorten_url(&self, page: &Page) -> Result<String, WikiError>
+    {
+        let resp: ShortenUrlResponse = self
+            .post_form_at(
+                META_API_URL,
+                &[
+                    ("action", "shortenurl"),
+                    ("format", "json"),
+                    ("url", &page.canonical_url()),
+                    ("formatversion", "2"),
+                ],
+            )
+            .await?;
+
+        match resp.shortenurl {
+            Some(x) => Ok(x.shorturl),
+            None => Err(WikiError::ResponseError),
+        }
+    }
+
+    /// Expand a short link like `https://w.wiki/3zz` to the URL it points to.
+    ///
+    /// Fails with [`WikiError::PageNotFoundError`] if there is no such short
+    /// link.
+    pub async fn resolve_short(&self, url: &str) -> Result<String, WikiError>
+    {
+        match self.redirect_target(url).await {
+            Ok(x) => Ok(x.to_string()),
+            Err(WikiError::HttpStatusError(404)) => Err(WikiError::PageNotFoundError(url.to_string())),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::ShortenUrlResponse;
+    use crate::{Page, WikiClient};
+
+    #[test]
+    fn test_parse_shorten_url()
+    {
+        let resp: ShortenUrlResponse = serde_json::from_str(
+            r#"{"shortenurl":{"shorturl":"https://w.wiki/3zz","shorturlalt":"https://w.wiki/_3zz"}}"#,
+        )
+        .unwrap();
+        assert_eq!(resp.shortenurl.unwrap().shorturl, "https://w.wiki/3zz");
+
+        let resp: ShortenUrlResponse = serde_json::from_str(
+            r#"{"error":{"code":"urlshortener-error-disallowed-url","info":"URLs to this domain are not allowed."}}"#,
+        )
+        .unwrap();
+        assert!(resp.shortenurl.is_none());
+        assert_eq!(resp.error.unwrap().code, "urlshortener-error-disallowed-url");
+    }
+
+    #[tokio::test]
+    async fn test_shorten_and_resolve()
+    {
+        let client = WikiClient::new();
+        let page = Page::new("Albert Einstein".to_string(), String::new());
+        let short = client.shorten_url(&page).await.unwrap();
+        assert!(short.starts_with("https://w.wiki/"));
+        assert_eq!(client.resolve_short(&short).await.unwrap(), page.canonical_url());
+    }
+}
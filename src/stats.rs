@@ -0,0 +1,100 @@
+//! Usage counters of a [`WikiClient`](crate::WikiClient), e.g. for services
+//! that report how much they use Wikipedia.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// How much a [`WikiClient`](crate::WikiClient) and its clones have used
+/// Wikipedia since the client was created, see
+/// [`WikiClient::stats`](crate::WikiClient::stats).
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClientStats
+{
+    /// Number of HTTP requests sent, including failed ones
+    pub requests: u64,
+
+    /// Number of bytes of response bodies received
+    pub bytes_received: u64,
+
+    /// Number of lookups answered from the cache without a request, including
+    /// remembered misses
+    pub cache_hits: u64,
+
+    /// Number of requests made again after a retryable error, e.g. by
+    /// [`WikiClient::watch`](crate::WikiClient::watch)
+    pub retries: u64,
+
+    /// Number of responses telling the client to slow down, i.e. with status
+    /// 429 (Too Many Requests) or 503 (Service Unavailable)
+    pub throttled: u64,
+}
+
+/// The counters behind [`ClientStats`], shared by the clones of a client
+#[derive(Debug, Default)]
+pub(crate) struct Counters
+{
+    requests:       AtomicU64,
+    bytes_received: AtomicU64,
+    cache_hits:     AtomicU64,
+    retries:        AtomicU64,
+    throttled:      AtomicU64,
+}
+
+impl Counters
+{
+    pub(crate) fn record_request(&self) { self.requests.fetch_add(1, Ordering::Relaxed); }
+
+    pub(crate) fn record_bytes(&self, bytes: usize) { self.bytes_received.fetch_add(bytes as u64, Ordering::Relaxed); }
+
+    pub(crate) fn record_cache_hit(&self) { self.cache_hits.fetch_add(1, Ordering::Relaxed); }
+
+    pub(crate) fn record_retry(&self) { self.retries.fetch_add(1, Ordering::Relaxed); }
+
+    /// Count a response with `status` as throttled if it asks to slow down
+    pub(crate) fn record_status(&self, status: u16)
+    {
+        if matches!(status, 429 | 503) {
+            self.throttled.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> ClientStats
+    {
+        ClientStats {
+            requests:       self.requests.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            cache_hits:     self.cache_hits.load(Ordering::Relaxed),
+            retries:        self.retries.load(Ordering::Relaxed),
+            throttled:      self.throttled.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{ClientStats, Counters};
+
+    #[test]
+    fn test_counters()
+    {
+        let counters = Counters::default();
+        counters.record_request();
+        counters.record_request();
+        counters.record_bytes(512);
+        counters.record_status(200);
+        counters.record_status(429);
+        counters.record_cache_hit();
+        counters.record_retry();
+
+        assert_eq!(
+            counters.snapshot(),
+            ClientStats {
+                requests:       2,
+                bytes_received: 512,
+                cache_hits:     1,
+                retries:        1,
+                throttled:      1,
+            }
+        );
+    }
+}
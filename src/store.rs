@@ -0,0 +1,420 @@
+//! Persisting fetched pages, summaries and revisions in SQLite, e.g. to build
+//! a corpus incrementally over several runs.
+
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::{
+    types::{format_timestamp, parse_timestamp},
+    Coordinates, Page, PageId, PageOverview, Revision, RevisionContent, Thumbnail, WikiError,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS pages (
+    lang  TEXT NOT NULL,
+    title TEXT NOT NULL,
+    url   TEXT NOT NULL,
+    PRIMARY KEY (lang, title)
+);
+CREATE TABLE IF NOT EXISTS summaries (
+    lang             TEXT NOT NULL,
+    pageid           INTEGER NOT NULL,
+    title            TEXT NOT NULL,
+    url              TEXT NOT NULL,
+    extract          TEXT NOT NULL,
+    lat              REAL,
+    lon              REAL,
+    thumbnail        TEXT,
+    thumbnail_width  INTEGER,
+    thumbnail_height INTEGER,
+    PRIMARY KEY (lang, pageid)
+);
+CREATE INDEX IF NOT EXISTS summaries_title ON summaries (lang, title);
+CREATE TABLE IF NOT EXISTS revisions (
+    lang      TEXT NOT NULL,
+    revid     INTEGER NOT NULL,
+    title     TEXT NOT NULL,
+    parentid  INTEGER NOT NULL,
+    user      TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    comment   TEXT NOT NULL,
+    size      INTEGER NOT NULL,
+    minor     INTEGER NOT NULL,
+    wikitext  TEXT NOT NULL,
+    PRIMARY KEY (lang, revid)
+);
+CREATE INDEX IF NOT EXISTS revisions_title ON revisions (lang, title);
+";
+
+const REVISION_COLUMNS: &str = "title, revid, parentid, user, timestamp, comment, size, minor, wikitext";
+
+/// A SQLite database of fetched pages, summaries and revisions.
+///
+/// Everything is stored per language, since the same title names different
+/// pages on different Wikipedias. Storing a value that's already stored
+/// replaces it, so refetched summaries stay current. Revisions are keyed by
+/// language and id, since ids are only unique within one wiki.
+///
+/// The database is accessed synchronously, so calls block. They're quick for
+/// a local file, but wrap them in `tokio::task::spawn_blocking` when storing
+/// large batches from async code.
+#[derive(Debug)]
+pub struct Store
+{
+    conn: Connection,
+}
+
+impl Store
+{
+    /// Open the database at `path`, creating it and its tables if they don't
+    /// exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, WikiError>
+    {
+        Self::init(Connection::open(path).map_err(store_error)?)
+    }
+
+    /// Open a database that's kept in memory and lost when dropped
+    pub fn open_in_memory() -> Result<Self, WikiError>
+    {
+        Self::init(Connection::open_in_memory().map_err(store_error)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, WikiError>
+    {
+        conn.execute_batch(SCHEMA).map_err(store_error)?;
+        Ok(Self { conn })
+    }
+
+    /// Store `page`, replacing the stored URL if the page is already stored
+    pub fn put_page(&self, page: &Page) -> Result<(), WikiError>
+    {
+        self.conn
+            .execute(
+                "INSERT INTO pages (lang, title, url) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (lang, title) DO UPDATE SET url = excluded.url",
+                params![&*page.lang, &*page.title, &*page.url],
+            )
+            .map_err(store_error)?;
+        Ok(())
+    }
+
+    /// Store the summary of a page of the `lang` Wikipedia, replacing the
+    /// stored summary of the same page
+    pub fn put_overview(&self, lang: &str, overview: &PageOverview) -> Result<(), WikiError>
+    {
+        let thumbnail = overview.thumbnail.as_ref();
+        self.conn
+            .execute(
+                "INSERT INTO summaries
+                 (lang, pageid, title, url, extract, lat, lon, thumbnail, thumbnail_width, thumbnail_height)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                 ON CONFLICT (lang, pageid) DO UPDATE SET
+                 title = excluded.title, url = excluded.url, extract = excluded.extract, lat = excluded.lat,
+                 lon = excluded.lon, thumbnail = excluded.thumbnail, thumbnail_width = excluded.thumbnail_width,
+                 thumbnail_height = excluded.thumbnail_height",
+                params![
+                    lang,
+                    overview.pageid.0,
+                    overview.title,
+                    overview.url,
+                    overview.extract,
+                    overview.coordinates.map(|x| x.lat),
+                    overview.coordinates.map(|x| x.lon),
+                    thumbnail.map(|x| &x.source),
+                    thumbnail.map(|x| x.width),
+                    thumbnail.map(|x| x.height),
+                ],
+            )
+            .map_err(store_error)?;
+        Ok(())
+    }
+
+    /// Store a revision of a page of the `lang` Wikipedia. Returns `true` if
+    /// the revision wasn't stored before, and `false` if it replaced the
+    /// stored revision of the `lang` Wikipedia with the same id.
+    pub fn put_revision(&self, lang: &str, content: &RevisionContent) -> Result<bool, WikiError>
+    {
+        let exists = self.has_revision(lang, content.revision.revid)?;
+        let revision = &content.revision;
+        self.conn
+            .execute(
+                "INSERT INTO revisions
+                 (revid, lang, title, parentid, user, timestamp, comment, size, minor, wikitext)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                 ON CONFLICT (lang, revid) DO UPDATE SET
+                 title = excluded.title, parentid = excluded.parentid, user = excluded.user,
+                 timestamp = excluded.timestamp, comment = excluded.comment, size = excluded.size,
+                 minor = excluded.minor, wikitext = excluded.wikitext",
+                params![
+                    revision.revid,
+                    lang,
+                    content.title,
+                    revision.parentid,
+                    revision.user,
+                    format_timestamp(&revision.timestamp),
+                    revision.comment,
+                    revision.size as i64,
+                    revision.minor,
+                    content.wikitext,
+                ],
+            )
+            .map_err(store_error)?;
+        Ok(!exists)
+    }
+
+    /// The stored page titled `title` of the `lang` Wikipedia
+    pub fn page(&self, lang: &str, title: &str) -> Result<Option<Page>, WikiError>
+    {
+        self.conn
+            .query_row(
+                "SELECT title, url FROM pages WHERE lang = ?1 AND title = ?2",
+                params![lang, title],
+                |row| Ok(Page::new(row.get(0)?, row.get(1)?).with_language(lang)),
+            )
+            .optional()
+            .map_err(store_error)
+    }
+
+    /// The titles of the stored pages of the `lang` Wikipedia, in
+    /// alphabetical order
+    pub fn titles(&self, lang: &str) -> Result<Vec<String>, WikiError>
+    {
+        let mut statement = self
+            .conn
+            .prepare("SELECT title FROM pages WHERE lang = ?1 ORDER BY title")
+            .map_err(store_error)?;
+        let titles = statement
+            .query_map(params![lang], |row| row.get(0))
+            .map_err(store_error)?
+            .collect::<Result<_, _>>()
+            .map_err(store_error)?;
+        Ok(titles)
+    }
+
+    /// The stored summary of the page titled `title` of the `lang` Wikipedia
+    pub fn overview(&self, lang: &str, title: &str) -> Result<Option<PageOverview>, WikiError>
+    {
+        self.conn
+            .query_row(
+                "SELECT title, pageid, url, extract, lat, lon, thumbnail, thumbnail_width, thumbnail_height
+                 FROM summaries WHERE lang = ?1 AND title = ?2",
+                params![lang, title],
+                to_overview,
+            )
+            .optional()
+            .map_err(store_error)
+    }
+
+    /// Whether the revision of the `lang` Wikipedia with the id `revid` is
+    /// stored, e.g. to skip fetching it again
+    pub fn has_revision(&self, lang: &str, revid: i64) -> Result<bool, WikiError>
+    {
+        self.conn
+            .query_row(
+                "SELECT 1 FROM revisions WHERE lang = ?1 AND revid = ?2",
+                params![lang, revid],
+                |_| Ok(()),
+            )
+            .optional()
+            .map(|x| x.is_some())
+            .map_err(store_error)
+    }
+
+    /// The stored revision of the `lang` Wikipedia with the id `revid`
+    pub fn revision(&self, lang: &str, revid: i64) -> Result<Option<RevisionContent>, WikiError>
+    {
+        self.conn
+            .query_row(
+                &format!("SELECT {REVISION_COLUMNS} FROM revisions WHERE lang = ?1 AND revid = ?2"),
+                params![lang, revid],
+                to_revision_content,
+            )
+            .optional()
+            .map_err(store_error)
+    }
+
+    /// The newest stored revision of the page titled `title` of the `lang`
+    /// Wikipedia
+    pub fn latest_revision(&self, lang: &str, title: &str) -> Result<Option<RevisionContent>, WikiError>
+    {
+        self.conn
+            .query_row(
+                &format!(
+                    "SELECT {REVISION_COLUMNS} FROM revisions WHERE lang = ?1 AND title = ?2 ORDER BY revid DESC \
+                     LIMIT 1"
+                ),
+                params![lang, title],
+                to_revision_content,
+            )
+            .optional()
+            .map_err(store_error)
+    }
+
+    /// The stored revisions of the page titled `title` of the `lang`
+    /// Wikipedia, oldest first, without their wikitext
+    pub fn revisions(&self, lang: &str, title: &str) -> Result<Vec<Revision>, WikiError>
+    {
+        let mut statement = self
+            .conn
+            .prepare(&format!(
+                "SELECT {REVISION_COLUMNS} FROM revisions WHERE lang = ?1 AND title = ?2 ORDER BY revid"
+            ))
+            .map_err(store_error)?;
+        let revisions = statement
+            .query_map(params![lang, title], |row| to_revision_content(row).map(|x| x.revision))
+            .map_err(store_error)?
+            .collect::<Result<_, _>>()
+            .map_err(store_error)?;
+        Ok(revisions)
+    }
+}
+
+fn to_overview(row: &Row) -> rusqlite::Result<PageOverview>
+{
+    let coordinates = match (row.get(4)?, row.get(5)?) {
+        (Some(lat), Some(lon)) => Some(Coordinates { lat, lon }),
+        _ => None,
+    };
+    let thumbnail = row.get::<_, Option<String>>(6)?.map(|source| {
+        Ok::<_, rusqlite::Error>(Thumbnail {
+            source,
+            width: row.get(7)?,
+            height: row.get(8)?,
+        })
+    });
+
+    Ok(PageOverview {
+        title: row.get(0)?,
+        pageid: PageId(row.get(1)?),
+        url: row.get(2)?,
+        extract: row.get(3)?,
+        coordinates,
+        thumbnail: thumbnail.transpose()?,
+    })
+}
+
+fn to_revision_content(row: &Row) -> rusqlite::Result<RevisionContent>
+{
+    let timestamp: String = row.get(4)?;
+    let timestamp = parse_timestamp(&timestamp)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(4, rusqlite::types::Type::Text, e.into()))?;
+
+    Ok(RevisionContent {
+        title:    row.get(0)?,
+        revision: Revision {
+            revid: row.get(1)?,
+            parentid: row.get(2)?,
+            user: row.get(3)?,
+            timestamp,
+            comment: row.get(5)?,
+            size: row.get::<_, i64>(6)? as u64,
+            minor: row.get(7)?,
+        },
+        wikitext: row.get(8)?,
+    })
+}
+
+fn store_error(e: rusqlite::Error) -> WikiError { WikiError::StoreError(e.to_string()) }
+
+#[cfg(test)]
+mod tests
+{
+    use super::Store;
+    use crate::{
+        types::parse_timestamp, Coordinates, Page, PageId, PageOverview, Revision, RevisionContent, Thumbnail,
+    };
+
+    fn revision(revid: i64, wikitext: &str) -> RevisionContent
+    {
+        RevisionContent {
+            title:    "Eiffel Tower".to_string(),
+            revision: Revision {
+                revid,
+                parentid: revid - 1,
+                user: "Example".to_string(),
+                timestamp: parse_timestamp("2023-05-01T12:00:00Z").unwrap(),
+                comment: "copyedit".to_string(),
+                size: wikitext.len() as u64,
+                minor: true,
+            },
+            wikitext: wikitext.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_store_revisions()
+    {
+        let store = Store::open_in_memory().unwrap();
+        assert!(store.put_revision("en", &revision(2, "second")).unwrap());
+        assert!(store.put_revision("en", &revision(1, "first")).unwrap());
+        assert!(!store.put_revision("en", &revision(2, "second, edited")).unwrap());
+
+        assert!(store.has_revision("en", 1).unwrap());
+        assert!(!store.has_revision("en", 3).unwrap());
+        assert_eq!(store.revision("en", 1).unwrap(), Some(revision(1, "first")));
+        assert_eq!(
+            store.latest_revision("en", "Eiffel Tower").unwrap(),
+            Some(revision(2, "second, edited"))
+        );
+        assert_eq!(store.latest_revision("fr", "Eiffel Tower").unwrap(), None);
+
+        let revids: Vec<i64> = store
+            .revisions("en", "Eiffel Tower")
+            .unwrap()
+            .into_iter()
+            .map(|x| x.revid)
+            .collect();
+        assert_eq!(revids, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_store_same_revid_in_two_languages()
+    {
+        let store = Store::open_in_memory().unwrap();
+        assert!(store.put_revision("en", &revision(2, "English")).unwrap());
+        assert!(!store.has_revision("fr", 2).unwrap());
+        assert!(store.put_revision("fr", &revision(2, "Français")).unwrap());
+
+        assert_eq!(store.revision("en", 2).unwrap(), Some(revision(2, "English")));
+        assert_eq!(store.revision("fr", 2).unwrap(), Some(revision(2, "Français")));
+        assert_eq!(store.revision("de", 2).unwrap(), None);
+    }
+
+    #[test]
+    fn test_store_pages_and_overviews()
+    {
+        let store = Store::open_in_memory().unwrap();
+        let page = Page::new(
+            "Tour Eiffel".to_string(),
+            "https://fr.wikipedia.org/wiki/Tour_Eiffel".to_string(),
+        )
+        .with_language("fr");
+        store.put_page(&page).unwrap();
+        assert_eq!(store.page("fr", "Tour Eiffel").unwrap(), Some(page));
+        assert_eq!(store.page("en", "Tour Eiffel").unwrap(), None);
+        assert_eq!(store.titles("fr").unwrap(), vec!["Tour Eiffel".to_string()]);
+
+        let mut overview = PageOverview {
+            title:       "Tour Eiffel".to_string(),
+            pageid:      PageId(1359783),
+            url:         "https://fr.wikipedia.org/wiki/Tour_Eiffel".to_string(),
+            extract:     "La tour Eiffel est une tour de fer puddlé.".to_string(),
+            coordinates: Some(Coordinates {
+                lat: 48.8583,
+                lon: 2.2944,
+            }),
+            thumbnail:   None,
+        };
+        store.put_overview("fr", &overview).unwrap();
+        assert_eq!(store.overview("fr", "Tour Eiffel").unwrap(), Some(overview.clone()));
+
+        overview.thumbnail = Some(Thumbnail {
+            source: "https://upload.wikimedia.org/tour.jpg".to_string(),
+            width:  320,
+            height: 480,
+        });
+        store.put_overview("fr", &overview).unwrap();
+        assert_eq!(store.overview("fr", "Tour Eiffel").unwrap(), Some(overview));
+    }
+}
@@ -0,0 +1,119 @@
+//! Local title normalization following MediaWiki's rules, so cache keys and
+//! comparisons are consistent before any network call.
+
+/// Namespace names and their aliases as used by the English Wikipedia, with
+/// the canonical name each normalizes to.
+const NAMESPACES: &[(&str, &str)] = &[
+    ("media", "Media"),
+    ("special", "Special"),
+    ("talk", "Talk"),
+    ("user", "User"),
+    ("user talk", "User talk"),
+    ("wikipedia", "Wikipedia"),
+    ("wp", "Wikipedia"),
+    ("project", "Wikipedia"),
+    ("wikipedia talk", "Wikipedia talk"),
+    ("wt", "Wikipedia talk"),
+    ("project talk", "Wikipedia talk"),
+    ("file", "File"),
+    ("image", "File"),
+    ("file talk", "File talk"),
+    ("image talk", "File talk"),
+    ("mediawiki", "MediaWiki"),
+    ("mediawiki talk", "MediaWiki talk"),
+    ("template", "Template"),
+    ("template talk", "Template talk"),
+    ("help", "Help"),
+    ("help talk", "Help talk"),
+    ("category", "Category"),
+    ("category talk", "Category talk"),
+    ("portal", "Portal"),
+    ("portal talk", "Portal talk"),
+    ("draft", "Draft"),
+    ("draft talk", "Draft talk"),
+    ("timedtext", "TimedText"),
+    ("timedtext talk", "TimedText talk"),
+    ("module", "Module"),
+    ("module talk", "Module talk"),
+];
+
+/// Normalize a page title the way MediaWiki does:
+///
+/// - underscores become spaces, and runs of whitespace collapse into one space
+/// - leading and trailing whitespace, a leading `:` and directional marks are
+///   removed
+/// - namespace prefixes are matched case-insensitively and aliases are replaced
+///   by the canonical name, e.g. `wp:` becomes `Wikipedia:` and `image:`
+///   becomes `File:`
+/// - the first letter of the title is capitalized. Only the first letter is, so
+///   `albert einstein` becomes `Albert einstein`, just like MediaWiki, which
+///   treats the rest of the title as case-sensitive.
+///
+/// ```
+/// use wikipedia_api::title::normalize;
+///
+/// assert_eq!(normalize("albert  einstein"), "Albert einstein");
+/// assert_eq!(normalize("image:eiffel_tower.jpg"), "File:Eiffel tower.jpg");
+/// ```
+pub fn normalize(title: &str) -> String
+{
+    let title: String = title
+        .chars()
+        .filter(|c| !matches!(c, '\u{200E}' | '\u{200F}'))
+        .map(|c| {
+            if c == '_' {
+                ' '
+            }
+            else {
+                c
+            }
+        })
+        .collect();
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    let title = title.strip_prefix(':').unwrap_or(&title).trim_start();
+
+    if let Some((prefix, rest)) = title.split_once(':') {
+        let prefix = prefix.trim().to_lowercase();
+        if let Some((_, namespace)) = NAMESPACES.iter().find(|(alias, _)| *alias == prefix) {
+            return format!("{namespace}:{}", capitalize(rest.trim_start()));
+        }
+    }
+
+    capitalize(title)
+}
+
+/// Uppercase the first letter of `s`
+fn capitalize(s: &str) -> String
+{
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::normalize;
+
+    #[test]
+    fn test_normalize_spacing_and_case()
+    {
+        assert_eq!(normalize("albert  einstein"), "Albert einstein");
+        assert_eq!(normalize("  Albert_Einstein "), "Albert Einstein");
+        assert_eq!(normalize("iPhone"), "IPhone");
+        assert_eq!(normalize("éclair"), "Éclair");
+        assert_eq!(normalize(""), "");
+    }
+
+    #[test]
+    fn test_normalize_namespaces()
+    {
+        assert_eq!(normalize("category : physics"), "Category:Physics");
+        assert_eq!(normalize("WP:NPOV"), "Wikipedia:NPOV");
+        assert_eq!(normalize("user_TALK:jimbo"), "User talk:Jimbo");
+        assert_eq!(normalize(":Category:Physics"), "Category:Physics");
+        assert_eq!(normalize("Star Wars: Episode IV"), "Star Wars: Episode IV");
+    }
+}
@@ -0,0 +1,501 @@
+//! Domain types shared across endpoints.
+//!
+//! These are the types the crate hands out, as opposed to the mirrors of raw
+//! API responses each endpoint deserializes into.
+
+use std::{collections::HashSet, fmt, marker::PhantomData, ops::Range};
+
+use serde::{
+    de::{MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+
+use crate::Page;
+
+/// A point in time returned by the API.
+///
+/// With the `chrono` feature, which is on by default, this is a
+/// `chrono::DateTime<Utc>`. Without it, it's the ISO 8601 string returned by
+/// the API.
+#[cfg(feature = "chrono")]
+pub type Timestamp = chrono::DateTime<chrono::Utc>;
+
+/// A point in time returned by the API.
+///
+/// With the `chrono` feature, which is on by default, this is a
+/// `chrono::DateTime<Utc>`. Without it, it's the ISO 8601 string returned by
+/// the API.
+#[cfg(not(feature = "chrono"))]
+pub type Timestamp = String;
+
+/// Parse an ISO 8601 timestamp returned by the API
+#[cfg(feature = "chrono")]
+pub(crate) fn parse_timestamp(s: &str) -> Result<Timestamp, String>
+{
+    s.parse().map_err(|e: chrono::ParseError| e.to_string())
+}
+
+/// Parse an ISO 8601 timestamp returned by the API
+#[cfg(not(feature = "chrono"))]
+pub(crate) fn parse_timestamp(s: &str) -> Result<Timestamp, String> { Ok(s.to_string()) }
+
+/// Format a timestamp the way the API expects it in parameters
+#[cfg(feature = "chrono")]
+pub(crate) fn format_timestamp(timestamp: &Timestamp) -> String
+{
+    timestamp.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+/// Format a timestamp the way the API expects it in parameters
+#[cfg(not(feature = "chrono"))]
+pub(crate) fn format_timestamp(timestamp: &Timestamp) -> String { timestamp.clone() }
+
+/// Deserialize an expiry, which is either a timestamp or `infinity`
+pub(crate) fn deserialize_expiry<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Timestamp>, D::Error>
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(x) if x != "infinity" && x != "infinite" => {
+            parse_timestamp(&x).map(Some).map_err(serde::de::Error::custom)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Deserialize the `pages` of a query, which are an array with
+/// `formatversion=2` and an object keyed by page id with `formatversion=1`, as
+/// returned by mirrors that don't support version 2
+pub(crate) fn deserialize_pages<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    struct PagesVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for PagesVisitor<T>
+    {
+        type Value = Vec<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "an array or object of pages") }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<T>, A::Error>
+        {
+            let mut pages = Vec::new();
+            while let Some(page) = seq.next_element()? {
+                pages.push(page);
+            }
+            Ok(pages)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Vec<T>, A::Error>
+        {
+            let mut pages = Vec::new();
+            while let Some((_, page)) = map.next_entry::<String, T>()? {
+                pages.push(page);
+            }
+            Ok(pages)
+        }
+    }
+
+    deserializer.deserialize_any(PagesVisitor(PhantomData))
+}
+
+/// Deserialize a flag like `missing`, which is `true` with `formatversion=2`
+/// and an empty string with `formatversion=1`, where it's left out when unset
+pub(crate) fn deserialize_flag<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error>
+{
+    Ok(!matches!(
+        serde_json::Value::deserialize(deserializer)?,
+        serde_json::Value::Bool(false) | serde_json::Value::Null
+    ))
+}
+
+/// A `normalized` or `redirects` entry of a query, mapping a requested title
+/// to the title the API used instead
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct TitleMapping
+{
+    pub from: String,
+    pub to:   String,
+}
+
+/// Follow the normalizations and redirects in `mappings` from `title` to the
+/// title of the page the API returned for it. Stops at the first title seen
+/// twice, so mappings that form a cycle can't loop forever.
+pub(crate) fn resolve_title(mappings: &[TitleMapping], title: &str) -> String
+{
+    let mut seen = HashSet::new();
+    let mut title = title.to_string();
+
+    while let Some(m) = mappings.iter().find(|m| m.from == title) {
+        if !seen.insert(title.clone()) {
+            break;
+        }
+        title = m.to.clone();
+    }
+
+    title
+}
+
+/// The id of a page. Unlike its title, it stays the same when the page is
+/// renamed.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PageId(pub i64);
+
+impl From<i64> for PageId
+{
+    fn from(id: i64) -> Self { Self(id) }
+}
+
+impl fmt::Display for PageId
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+}
+
+/// A protection applied to a page.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Protection
+{
+    /// The protected action, e.g. `edit` or `move`
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    /// The user group required to perform the action, e.g. `autoconfirmed` or
+    /// `sysop`
+    pub level: String,
+
+    /// When the protection expires, `None` if it never does
+    #[serde(deserialize_with = "deserialize_expiry")]
+    pub expiry: Option<Timestamp>,
+}
+
+/// Geographic coordinates of the subject of a page.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Coordinates
+{
+    /// Latitude in degrees
+    pub lat: f64,
+
+    /// Longitude in degrees
+    pub lon: f64,
+}
+
+/// A thumbnail of the lead image of a page.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Thumbnail
+{
+    /// The URL of the thumbnail
+    pub source: String,
+
+    /// Width in pixels
+    pub width: u32,
+
+    /// Height in pixels
+    pub height: u32,
+}
+
+/// An excerpt of a page around the matched search terms.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snippet
+{
+    /// The excerpt, either as HTML or as plain text depending on the
+    /// [`SnippetFormat`](crate::SnippetFormat)
+    pub text: String,
+
+    /// Byte ranges of the matched terms in `text`. Only filled in for
+    /// [`SnippetFormat::Plain`](crate::SnippetFormat::Plain).
+    pub highlights: Vec<Range<usize>>,
+}
+
+impl Snippet
+{
+    /// Convert the snippet HTML returned by the API to plain text, recording
+    /// where the `<span class="searchmatch">` elements were.
+    pub fn from_html(html: &str) -> Self
+    {
+        const MATCH_START: &str = "<span class=\"searchmatch\">";
+
+        let mut snippet = Self::default();
+        let mut match_start = None;
+        let mut rest = html;
+
+        while let Some(c) = rest.chars().next() {
+            if rest.starts_with(MATCH_START) {
+                match_start = Some(snippet.text.len());
+                rest = &rest[MATCH_START.len()..];
+            }
+            else if c == '<' {
+                let end = rest.find('>').map_or(rest.len(), |x| x + 1);
+                if rest.starts_with("</span>") {
+                    if let Some(start) = match_start.take() {
+                        snippet.highlights.push(start..snippet.text.len());
+                    }
+                }
+                rest = &rest[end..];
+            }
+            else if c == '&' {
+                let entity = rest
+                    .find(';')
+                    .and_then(|end| Some((decode_entity(&rest[1..end])?, end)));
+                match entity {
+                    Some((decoded, end)) => {
+                        snippet.text.push(decoded);
+                        rest = &rest[end + 1..];
+                    }
+                    None => {
+                        snippet.text.push(c);
+                        rest = &rest[1..];
+                    }
+                }
+            }
+            else {
+                snippet.text.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+
+        snippet
+    }
+}
+
+/// Decode an HTML entity, given without the surrounding `&` and `;`
+fn decode_entity(entity: &str) -> Option<char>
+{
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{A0}'),
+        _ => {
+            let code = match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => entity.strip_prefix('#')?.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+/// A result of a search.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchHit
+{
+    /// Title of the page
+    pub title: String,
+
+    /// Id of the page
+    pub pageid: PageId,
+
+    /// Size of the page in bytes
+    pub size: u64,
+
+    /// Number of words in the page
+    pub wordcount: u64,
+
+    /// Excerpt of the page around the matched terms
+    pub snippet: Snippet,
+
+    /// When the page was last edited, if the search reported it
+    #[serde(default)]
+    pub timestamp: Option<Timestamp>,
+
+    /// Titles of redirects to the page that matched the search, e.g.
+    /// `Einstein` for `Albert Einstein`
+    #[serde(default)]
+    pub aliases: Vec<String>,
+
+    /// How closely the title or an alias matches the query, from 0 to 1, if the
+    /// hit was re-ranked with [`rerank`](crate::search::rerank)
+    #[serde(default)]
+    pub score: Option<f64>,
+}
+
+/// A category a page belongs to.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Category
+{
+    /// Title of the category, including the `Category:` prefix
+    pub title: String,
+
+    /// Whether the category is hidden from readers, as maintenance categories
+    /// usually are
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub hidden: bool,
+}
+
+/// A revision of a page.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Revision
+{
+    /// Id of the revision
+    pub revid: i64,
+
+    /// Id of the revision before this one, `0` if this revision created the
+    /// page
+    #[serde(default)]
+    pub parentid: i64,
+
+    /// Name of the user who made the revision
+    #[serde(default)]
+    pub user: String,
+
+    /// When the revision was made
+    pub timestamp: Timestamp,
+
+    /// The edit summary
+    #[serde(default)]
+    pub comment: String,
+
+    /// Size of the page after the revision, in bytes
+    #[serde(default)]
+    pub size: u64,
+
+    /// Whether the revision was marked as a minor edit
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub minor: bool,
+}
+
+/// A link to the same subject on the Wikipedia of another language.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LangLink
+{
+    /// Language code of the other Wikipedia, e.g. `de`
+    pub lang: String,
+
+    /// Title of the page on the other Wikipedia
+    pub title: String,
+
+    /// The URL of the page on the other Wikipedia
+    #[serde(default)]
+    pub url: String,
+
+    /// Name of the language in the language of the wiki that was queried,
+    /// e.g. `German`
+    #[serde(default)]
+    pub langname: String,
+
+    /// Name of the language in that language, e.g. `Deutsch`
+    #[serde(default)]
+    pub autonym: String,
+}
+
+impl LangLink
+{
+    /// The linked page as a [`Page`] on the Wikipedia of its language. Links to
+    /// a section keep it, see [`Page::anchor`].
+    pub fn page(&self) -> Page { Page::new(self.title.clone(), self.url.clone()).with_language(&self.lang) }
+}
+
+/// Information about an uploaded file.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageInfo
+{
+    /// The URL of the file itself
+    pub url: String,
+
+    /// The URL of the file's description page
+    #[serde(default)]
+    pub descriptionurl: String,
+
+    /// Width in pixels, `0` for files that aren't images or videos
+    #[serde(default)]
+    pub width: u32,
+
+    /// Height in pixels, `0` for files that aren't images or videos
+    #[serde(default)]
+    pub height: u32,
+
+    /// Size of the file in bytes
+    #[serde(default)]
+    pub size: u64,
+
+    /// MIME type of the file, e.g. `image/jpeg`
+    #[serde(default)]
+    pub mime: String,
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{resolve_title, LangLink, Revision, Snippet, TitleMapping};
+
+    #[test]
+    fn test_snippet_from_html()
+    {
+        let snippet = Snippet::from_html(
+            "of the theory of <span class=\"searchmatch\">relativity</span> &quot;<span \
+             class=\"searchmatch\">Einstein</span>&#039;s&quot; –",
+        );
+        assert_eq!(snippet.text, "of the theory of relativity \"Einstein's\" –");
+        assert_eq!(snippet.highlights.len(), 2);
+        assert_eq!(&snippet.text[snippet.highlights[0].clone()], "relativity");
+        assert_eq!(&snippet.text[snippet.highlights[1].clone()], "Einstein");
+    }
+
+    #[test]
+    fn test_snippet_from_html_unknown_entity()
+    {
+        let snippet = Snippet::from_html("AT&T &bogus; <b>bold</b>");
+        assert_eq!(snippet.text, "AT&T &bogus; bold");
+        assert!(snippet.highlights.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_title()
+    {
+        let mapping = |from: &str, to: &str| {
+            TitleMapping {
+                from: from.to_string(),
+                to:   to.to_string(),
+            }
+        };
+
+        let mappings = [mapping("usa", "USA"), mapping("USA", "United States")];
+        assert_eq!(resolve_title(&mappings, "usa"), "United States");
+        assert_eq!(resolve_title(&mappings, "Paris"), "Paris");
+
+        let cycle = [mapping("A", "B"), mapping("B", "A"), mapping("C", "C")];
+        assert_eq!(resolve_title(&cycle, "A"), "A");
+        assert_eq!(resolve_title(&cycle, "C"), "C");
+    }
+
+    #[test]
+    fn test_parse_revision()
+    {
+        let revision: Revision = serde_json::from_str(
+            r#"{"revid":1150000000,"parentid":1149999999,"minor":true,"user":"Someone",
+            "timestamp":"2023-04-20T10:00:00Z","size":180000,"comment":"typo"}"#,
+        )
+        .unwrap();
+        assert_eq!(revision.parentid, 1149999999);
+        assert!(revision.minor);
+
+        let v1: Revision = serde_json::from_str(
+            r#"{"revid":1150000000,"parentid":1149999999,"minor":"","user":"Someone",
+            "timestamp":"2023-04-20T10:00:00Z","size":180000,"comment":"typo"}"#,
+        )
+        .unwrap();
+        assert_eq!(v1, revision);
+
+        let major: Revision = serde_json::from_str(
+            r#"{"revid":1149999999,"parentid":1149999998,"user":"Someone",
+            "timestamp":"2023-04-19T10:00:00Z","size":179990,"comment":"expand"}"#,
+        )
+        .unwrap();
+        assert!(!major.minor);
+    }
+
+    #[test]
+    fn test_parse_lang_link()
+    {
+        let link: LangLink = serde_json::from_str(
+            r#"{"lang":"de","url":"https://de.wikipedia.org/wiki/Albert_Einstein","langname":"German",
+            "autonym":"Deutsch","title":"Albert Einstein"}"#,
+        )
+        .unwrap();
+        assert_eq!(link.lang, "de");
+        assert_eq!(link.autonym, "Deutsch");
+    }
+}
@@ -0,0 +1,307 @@
+//! Watching a set of pages for edits, e.g. for bots that post a notification
+//! whenever an article changes.
+
+use std::{collections::HashMap, time::Duration, vec};
+
+use futures::{stream, Stream};
+use log::warn;
+
+use crate::{revisions::RevisionsResponse, AuthSession, Timestamp, WikiClient, WikiError};
+
+/// The longest the poll interval is stretched to while the API keeps failing
+const MAX_BACKOFF_FACTOR: u32 = 16;
+
+/// Options for [`WikiClient::watch`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct WatchOptions
+{
+    interval: Duration,
+    session:  Option<AuthSession>,
+}
+
+impl Default for WatchOptions
+{
+    fn default() -> Self
+    {
+        Self {
+            interval: Duration::from_secs(60),
+            session:  None,
+        }
+    }
+}
+
+impl WatchOptions
+{
+    /// Create `WatchOptions` with the defaults: poll every minute, anonymously.
+    pub fn new() -> Self { Self::default() }
+
+    /// How long to wait between polls. Values below one second are treated as
+    /// one second.
+    pub fn interval(mut self, interval: Duration) -> Self
+    {
+        self.interval = interval.max(Duration::from_secs(1));
+        self
+    }
+
+    /// Poll as the user of `session`, so each request holds 500 titles instead
+    /// of 50 if it's a [bot session](AuthSession::bot)
+    pub fn session(mut self, session: AuthSession) -> Self
+    {
+        self.session = Some(session);
+        self
+    }
+}
+
+/// A new revision of a watched page.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct PageChange
+{
+    /// Title of the page
+    pub title: String,
+
+    /// Id of the new revision
+    pub revid: i64,
+
+    /// Id of the revision seen at the poll before, `0` if the page didn't
+    /// exist then
+    pub previous_revid: i64,
+
+    /// Name of the user who made the new revision
+    pub user: String,
+
+    /// The edit summary of the new revision
+    pub comment: String,
+
+    /// When the new revision was made
+    pub timestamp: Timestamp,
+}
+
+impl WikiClient
+{
+    /// Watch the pages titled `titles` and stream a [`PageChange`] whenever
+    /// one of them has a new revision, polling at the interval of `options`.
+    ///
+    /// The first poll only records the current revisions. Each poll requests
+    /// the watched pages in as few batched requests as the API allows, and
+    /// reports the latest revision of each page that changed, so several
+    /// edits between two polls are reported as one change. While Wikipedia
+    /// fails with a [retryable](WikiError::is_retryable) error, e.g. when
+    /// it's rate limiting, the interval is doubled, up to 16 times, instead of
+    /// yielding the error. Other errors are yielded, and watching goes on.
+    /// The stream never ends.
+    pub fn watch(&self, titles: &[&str], options: &WatchOptions) -> impl Stream<Item = Result<PageChange, WikiError>>
+    {
+        struct State
+        {
+            client:         WikiClient,
+            titles:         Vec<String>,
+            options:        WatchOptions,
+            seen:           HashMap<String, i64>,
+            buffer:         vec::IntoIter<PageChange>,
+            polled:         bool,
+            // Whether a poll succeeded and recorded the revisions to compare
+            // with, which polls that failed didn't
+            baseline_taken: bool,
+            backoff:        u32,
+        }
+
+        let state = State {
+            client:         self.clone(),
+            titles:         titles.iter().map(|x| x.to_string()).collect(),
+            options:        options.clone(),
+            seen:           HashMap::new(),
+            buffer:         Vec::new().into_iter(),
+            polled:         false,
+            baseline_taken: false,
+            backoff:        1,
+        };
+
+        stream::unfold(state, |mut state| {
+            async move {
+                loop {
+                    if let Some(change) = state.buffer.next() {
+                        return Some((Ok(change), state));
+                    }
+                    if state.polled {
+                        tokio::time::sleep(state.options.interval * state.backoff).await;
+                    }
+
+                    let titles: Vec<&str> = state.titles.iter().map(String::as_str).collect();
+                    let result = state
+                        .client
+                        .latest_revisions(&titles, state.options.session.as_ref())
+                        .await;
+                    match result {
+                        Ok(responses) => {
+                            let changes = detect_changes(&mut state.seen, responses, !state.baseline_taken);
+                            state.buffer = changes.into_iter();
+                            state.polled = true;
+                            state.baseline_taken = true;
+                            state.backoff = 1;
+                        }
+                        Err(e) if e.is_retryable() => {
+                            warn!("Polling watched pages failed, backing off: {e}");
+                            state.client.counters().record_retry();
+                            state.polled = true;
+                            state.backoff = (state.backoff * 2).min(MAX_BACKOFF_FACTOR);
+                        }
+                        Err(e) => {
+                            state.polled = true;
+                            return Some((Err(e), state));
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Get the latest revision of each of the pages titled `titles`
+    async fn latest_revisions(
+        &self,
+        titles: &[&str],
+        session: Option<&AuthSession>,
+    ) -> Result<Vec<RevisionsResponse>, WikiError>
+    {
+        self.get_json_batched(
+            &[
+                ("action", "query"),
+                ("format", "json"),
+                ("prop", "revisions"),
+                ("rvprop", "ids|user|timestamp|comment"),
+                ("redirects", "1"),
+                ("formatversion", "2"),
+            ],
+            titles,
+            session,
+            |_: &RevisionsResponse| None,
+        )
+        .await
+    }
+}
+
+/// Record the latest revisions in `responses` in `seen`, and return the pages
+/// whose revision differs from the one seen before. Nothing is returned for
+/// the `baseline` poll.
+fn detect_changes(seen: &mut HashMap<String, i64>, responses: Vec<RevisionsResponse>, baseline: bool)
+    -> Vec<PageChange>
+{
+    let mut changes = Vec::new();
+    for page in responses.into_iter().flat_map(|x| x.query.pages) {
+        let Some(revision) = page.revisions.into_iter().next().map(|x| x.revision)
+        else {
+            continue;
+        };
+
+        let previous_revid = seen.insert(page.title.clone(), revision.revid);
+        if baseline || previous_revid == Some(revision.revid) {
+            continue;
+        }
+        changes.push(PageChange {
+            title:          page.title,
+            revid:          revision.revid,
+            previous_revid: previous_revid.unwrap_or(0),
+            user:           revision.user,
+            comment:        revision.comment,
+            timestamp:      revision.timestamp,
+        });
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::{
+        collections::HashMap,
+        io::{Read, Write},
+        net::TcpListener,
+        thread,
+        time::Duration,
+    };
+
+    use futures::StreamExt;
+
+    use super::{detect_changes, WatchOptions};
+    use crate::{revisions::RevisionsResponse, WikiClient};
+
+    fn poll(revids: &[(&str, i64)]) -> Vec<RevisionsResponse>
+    {
+        let pages: Vec<String> = revids
+            .iter()
+            .map(|(title, revid)| {
+                format!(
+                    r#"{{"ns":0,"title":"{title}","revisions":[{{"revid":{revid},"parentid":1,"user":"Example",
+                    "timestamp":"2023-05-01T12:00:00Z","comment":"copyedit"}}]}}"#
+                )
+            })
+            .collect();
+        let json = format!(
+            r#"{{"batchcomplete":true,"query":{{"pages":[{},{{"ns":0,"title":"Nowhere at all","missing":true}}]}}}}"#,
+            pages.join(",")
+        );
+        vec![serde_json::from_str(&json).unwrap()]
+    }
+
+    #[test]
+    fn test_detect_changes()
+    {
+        let mut seen = HashMap::new();
+        assert!(detect_changes(&mut seen, poll(&[("Paris", 10), ("Berlin", 20)]), true).is_empty());
+        assert!(detect_changes(&mut seen, poll(&[("Paris", 10), ("Berlin", 20)]), false).is_empty());
+
+        let changes = detect_changes(&mut seen, poll(&[("Paris", 10), ("Berlin", 21), ("Rome", 30)]), false);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].title, "Berlin");
+        assert_eq!((changes[0].previous_revid, changes[0].revid), (20, 21));
+        assert_eq!(changes[0].user, "Example");
+        assert_eq!((changes[1].previous_revid, changes[1].revid), (0, 30));
+    }
+
+    #[tokio::test]
+    async fn test_watch_takes_baseline_after_failed_poll()
+    {
+        // Fail the first poll with a retryable error, then answer the next one
+        let body = r#"{"batchcomplete":true,"query":{"pages":[{"ns":0,"title":"Paris","revisions":[{"revid":10,
+            "parentid":1,"user":"Example","timestamp":"2023-05-01T12:00:00Z","comment":"copyedit"}]}]}}"#;
+        let responses = [
+            "HTTP/1.1 503 Service Unavailable\r\nConnection: close\r\nContent-Length: 0\r\n\r\n".to_string(),
+            format!(
+                "HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Type: application/json\r\nContent-Length: \
+                 {}\r\n\r\n{body}",
+                body.len()
+            ),
+        ];
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let api_url = format!("http://{}/w/api.php", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0; 4096];
+                let _ = stream.read(&mut request);
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        let client = WikiClient::builder().api_url(&api_url).build().unwrap();
+        let options = WatchOptions::new().interval(Duration::from_secs(1));
+        let mut changes = Box::pin(client.watch(&["Paris"], &options));
+
+        // The successful poll comes two seconds after the failed one, since the
+        // interval is doubled while backing off
+        let change = tokio::time::timeout(Duration::from_secs(4), changes.next()).await;
+        assert!(change.is_err(), "the first successful poll is only a baseline");
+        assert!(server.is_finished());
+    }
+
+    #[tokio::test]
+    async fn test_watch()
+    {
+        // The first poll is only a baseline, so a watch can't yield anything
+        // before the second one
+        let options = WatchOptions::new().interval(Duration::from_secs(1));
+        let client = WikiClient::new();
+        let mut changes = Box::pin(client.watch(&["Albert Einstein"], &options));
+        let first = tokio::time::timeout(Duration::from_millis(500), changes.next()).await;
+        assert!(first.is_err());
+    }
+}
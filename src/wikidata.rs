@@ -0,0 +1,127 @@
+//! Finding the article on a Wikidata item, e.g. for data keyed by QIDs.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{types::deserialize_flag, ApiError, Page, WikiClient, WikiError};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RSitelink
+{
+    pub title: String,
+    #[serde(default)]
+    pub url:   String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct REntity
+{
+    #[serde(default, deserialize_with = "deserialize_flag")]
+    pub missing:   bool,
+    #[serde(default)]
+    pub sitelinks: HashMap<String, RSitelink>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct EntitiesResponse
+{
+    #[serde(default)]
+    pub entities: HashMap<String, REntity>,
+    pub error:    Option<ApiError>,
+}
+
+impl WikiClient
+{
+    /// Get the article on the Wikidata item `qid`, e.g. `Q937`, on the
+    /// Wikipedia of `lang`. The returned page uses this client, switched to
+    /// `lang`.
+    ///
+    /// Fails with [`WikiError::PageNotFoundError`] if the item doesn't exist
+    /// or has no article in that language.
+    pub async fn page_for_qid(&self, qid: &str, lang: &str) -> Result<Page, WikiError>
+    {
+        let site = wiki_site(lang);
+        let resp: EntitiesResponse = self
+            .get_json_at(
+                &self.wikidata_api_url(),
+                &[
+                    ("action", "wbgetentities"),
+                    ("format", "json"),
+                    ("ids", qid.trim()),
+                    ("props", "sitelinks/urls"),
+                    ("sitefilter", &site),
+                ],
+            )
+            .await?;
+
+        let sitelink = to_sitelink(qid, &site, resp)?;
+        Ok(Page::new(sitelink.title, sitelink.url).with_client(&self.clone().with_language(lang)))
+    }
+}
+
+/// The Wikidata site id of the Wikipedia of `lang`, e.g. `enwiki`. Dashes in
+/// language codes are underscores in site ids.
+fn wiki_site(lang: &str) -> String { format!("{}wiki", lang.replace('-', "_")) }
+
+/// Get the sitelink to `site` out of the response of
+/// [`WikiClient::page_for_qid`]
+fn to_sitelink(qid: &str, site: &str, resp: EntitiesResponse) -> Result<RSitelink, WikiError>
+{
+    let not_found = || WikiError::PageNotFoundError(format!("{qid} on {site}"));
+    match resp.error {
+        Some(e) if e.code == "no-such-entity" || e.code == "param-invalid" => return Err(not_found()),
+        Some(_) => return Err(WikiError::ResponseError),
+        None => {}
+    }
+
+    resp.entities
+        .into_values()
+        .find(|x| !x.missing)
+        .and_then(|mut x| x.sitelinks.remove(site))
+        .ok_or_else(not_found)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{to_sitelink, wiki_site, EntitiesResponse};
+    use crate::{WikiClient, WikiError};
+
+    #[test]
+    fn test_parse_sitelink()
+    {
+        let resp: EntitiesResponse = serde_json::from_str(
+            r#"{"entities":{"Q937":{"type":"item","id":"Q937","sitelinks":{"dewiki":{"site":"dewiki",
+            "title":"Albert Einstein","badges":["Q17437796"],"url":"https://de.wikipedia.org/wiki/Albert_Einstein"}}}},
+            "success":1}"#,
+        )
+        .unwrap();
+        let sitelink = to_sitelink("Q937", "dewiki", resp.clone()).unwrap();
+        assert_eq!(sitelink.title, "Albert Einstein");
+        assert_eq!(sitelink.url, "https://de.wikipedia.org/wiki/Albert_Einstein");
+
+        let err = to_sitelink("Q937", "frwiki", resp).err().unwrap();
+        assert_eq!(err, WikiError::PageNotFoundError("Q937 on frwiki".to_string()));
+
+        let missing: EntitiesResponse = serde_json::from_str(
+            r#"{"error":{"code":"no-such-entity","info":"Could not find an entity with the ID \"Q0\".",
+            "id":"Q0"},"servedby":"mw-api-ext.eqiad.main-7d"}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            to_sitelink("Q0", "dewiki", missing),
+            Err(WikiError::PageNotFoundError(_))
+        ));
+
+        assert_eq!(wiki_site("zh-yue"), "zh_yuewiki");
+    }
+
+    #[tokio::test]
+    async fn test_page_for_qid()
+    {
+        let page = WikiClient::new().page_for_qid("Q937", "de").await.unwrap();
+        assert_eq!(&*page.title, "Albert Einstein");
+        assert_eq!(&*page.lang, "de");
+    }
+}
@@ -0,0 +1,147 @@
+//! Dictionary definitions from Wiktionary.
+
+use std::collections::HashMap;
+
+use percent_encoding::utf8_percent_encode;
+use serde::{Deserialize, Serialize};
+
+use crate::{Snippet, WikiClient, WikiError, TITLE_ENCODE_SET};
+
+/// The definitions of a word as one part of speech, e.g. "serendipity" as a
+/// noun.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartOfSpeech
+{
+    /// The part of speech, e.g. `Noun`
+    pub part_of_speech: String,
+
+    /// The name of the language the word belongs to, e.g. `English`
+    pub language: String,
+
+    /// The definitions, in the order Wiktionary lists them
+    pub definitions: Vec<Definition>,
+}
+
+/// One definition of a word.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Definition
+{
+    /// Plain text of the definition
+    pub definition: String,
+
+    /// Plain text of the usage examples
+    pub examples: Vec<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RPartOfSpeech
+{
+    pub part_of_speech: String,
+    pub language:       String,
+    #[serde(default)]
+    pub definitions:    Vec<RDefinition>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RDefinition
+{
+    pub definition: String,
+    #[serde(default)]
+    pub examples:   Vec<String>,
+}
+
+/// The definitions by language code of the word
+pub(crate) type DefinitionResponse = HashMap<String, Vec<RPartOfSpeech>>;
+
+impl From<RPartOfSpeech> for PartOfSpeech
+{
+    fn from(part: RPartOfSpeech) -> Self
+    {
+        Self {
+            part_of_speech: part.part_of_speech,
+            language:       part.language,
+            definitions:    part
+                .definitions
+                .into_iter()
+                .map(|x| {
+                    Definition {
+                        definition: Snippet::from_html(&x.definition).text,
+                        examples:   x.examples.iter().map(|x| Snippet::from_html(x).text).collect(),
+                    }
+                })
+                // Entries sometimes contain empty definitions left over from
+                // templates that didn't render
+                .filter(|x| !x.definition.trim().is_empty())
+                .collect(),
+        }
+    }
+}
+
+impl WikiClient
+{
+    /// Look up the definitions of `term` as a word of the language `lang`
+    /// (e.g. `en`), grouped by part of speech, on the Wiktionary in the
+    /// client's language.
+    ///
+    /// Fails with [`WikiError::PageNotFoundError`] if Wiktionary has no entry
+    /// for `term` in `lang`. Only the English Wiktionary offers definitions
+    /// through its REST API, though it has entries for words of most
+    /// languages.
+    pub async fn define(&self, term: &str, lang: &str) -> Result<Vec<PartOfSpeech>, WikiError>
+    {
+        let url = format!(
+            "{}/page/definition/{}",
+            self.sister_rest_url("wiktionary"),
+            utf8_percent_encode(&term.trim().replace(' ', "_"), TITLE_ENCODE_SET)
+        );
+
+        let mut resp: DefinitionResponse = match self.get_rest_json(&url).await {
+            Ok(x) => x,
+            Err(WikiError::HttpStatusError(404)) => return Err(WikiError::PageNotFoundError(term.to_string())),
+            Err(e) => return Err(e),
+        };
+
+        match resp.remove(lang) {
+            Some(parts) if !parts.is_empty() => Ok(parts.into_iter().map(PartOfSpeech::from).collect()),
+            _ => Err(WikiError::PageNotFoundError(term.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{DefinitionResponse, PartOfSpeech};
+    use crate::WikiClient;
+
+    #[test]
+    fn test_parse_definitions()
+    {
+        let mut resp: DefinitionResponse = serde_json::from_str(
+            r#"{"en":[{"partOfSpeech":"Noun","language":"English","definitions":[{"definition":
+            "An unsought, unintended, and/or unexpected, but fortunate, <a href=\"/wiki/discovery\">discovery</a>",
+            "parsedExamples":[{"example":"It was pure serendipity."}],"examples":[
+            "It was pure <b>serendipity</b>."]},{"definition":""}]}],
+            "fr":[{"partOfSpeech":"Noun","language":"French","definitions":[{"definition":"serendipity"}]}]}"#,
+        )
+        .unwrap();
+
+        let parts: Vec<PartOfSpeech> = resp.remove("en").unwrap().into_iter().map(Into::into).collect();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].part_of_speech, "Noun");
+        assert_eq!(parts[0].language, "English");
+        assert_eq!(parts[0].definitions.len(), 1);
+        assert!(parts[0].definitions[0].definition.contains("fortunate, discovery"));
+        assert_eq!(parts[0].definitions[0].examples, ["It was pure serendipity."]);
+    }
+
+    #[tokio::test]
+    async fn test_define()
+    {
+        let parts = WikiClient::new().define("serendipity", "en").await.unwrap();
+        assert!(parts
+            .iter()
+            .any(|x| x.part_of_speech == "Noun" && !x.definitions.is_empty()));
+    }
+}